@@ -0,0 +1,45 @@
+use hexdump::{Dump, OutputFormat, VisuMode};
+use std::io::{self, BufWriter};
+use std::time::Instant;
+
+// Renders a 64 MB dump into io::sink and reports the throughput, run with: cargo bench
+
+const SIZE: usize = 64 << 20;
+
+fn main() {
+    // measure the colored output, like on a terminal
+    colored::control::set_override(true);
+
+    // pseudo random bytes, so every visualization highlights a mix of bytes
+    let data: Vec<u8> = (0..SIZE as u32)
+        .map(|i| (i.wrapping_mul(2654435761) >> 24) as u8)
+        .collect();
+
+    let cases = [
+        ("default", VisuMode::Default, OutputFormat::Default, 1),
+        ("-v ascii", VisuMode::AsciiGraphic, OutputFormat::Default, 1),
+        (
+            "-v zeros",
+            VisuMode::HighlightZeros,
+            OutputFormat::Default,
+            1,
+        ),
+        ("-j 0", VisuMode::AsciiGraphic, OutputFormat::Default, 0),
+        ("-f jsonl", VisuMode::Default, OutputFormat::JsonLines, 1),
+    ];
+    for (name, visu_mode, output_format, threads) in cases {
+        let dump = Dump::from_bytes(data.clone())
+            .mode(visu_mode)
+            .format(output_format)
+            .threads(threads);
+        let start = Instant::now();
+        dump.write_to(BufWriter::with_capacity(1 << 16, io::sink()))
+            .unwrap();
+        let seconds = start.elapsed().as_secs_f64();
+        println!(
+            "{name:<10} {:>8.1} MB/s ({seconds:.2} s for {} MB)",
+            SIZE as f64 / (1 << 20) as f64 / seconds,
+            SIZE >> 20
+        );
+    }
+}
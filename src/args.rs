@@ -1,35 +1,417 @@
 use clap::Parser;
 
-fn parse_int(src: &str) -> Result<u64, String> {
-    if let Some(hex) = src.strip_prefix("0x") {
-        u64::from_str_radix(hex, 16).map_err(|e| e.to_string())
+// Address expressions: numbers (decimal, 0x hex, 0b binary, 0o octal, _ separators,
+// K/M/G/T size suffixes) combined with + - * / and parentheses, eg: 0x1000+0x20*3
+
+struct ExprParser<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl ExprParser<'_> {
+    fn peek(&mut self) -> Option<u8> {
+        while self.src.get(self.pos) == Some(&b' ') {
+            self.pos += 1;
+        }
+        self.src.get(self.pos).copied()
+    }
+
+    fn unexpected(&mut self) -> String {
+        match self.peek() {
+            Some(c) => format!("unexpected '{}' at position {}", c as char, self.pos + 1),
+            None => "unexpected end of expression".to_string(),
+        }
+    }
+
+    // sum = term (('+' | '-') term)*
+    fn sum(&mut self) -> Result<u64, String> {
+        let mut value = self.term()?;
+        loop {
+            match self.peek() {
+                Some(b'+') => {
+                    self.pos += 1;
+                    value = value
+                        .checked_add(self.term()?)
+                        .ok_or("result exceeds 64 bits")?;
+                }
+                Some(b'-') => {
+                    self.pos += 1;
+                    value = value
+                        .checked_sub(self.term()?)
+                        .ok_or("result is negative")?;
+                }
+                _ => return Ok(value),
+            }
+        }
+    }
+
+    // term = factor (('*' | '/') factor)*
+    fn term(&mut self) -> Result<u64, String> {
+        let mut value = self.factor()?;
+        loop {
+            match self.peek() {
+                Some(b'*') => {
+                    self.pos += 1;
+                    value = value
+                        .checked_mul(self.factor()?)
+                        .ok_or("result exceeds 64 bits")?;
+                }
+                Some(b'/') => {
+                    self.pos += 1;
+                    value = value
+                        .checked_div(self.factor()?)
+                        .ok_or("division by zero")?;
+                }
+                _ => return Ok(value),
+            }
+        }
+    }
+
+    // factor = '(' sum ')' | number
+    fn factor(&mut self) -> Result<u64, String> {
+        match self.peek() {
+            Some(b'(') => {
+                self.pos += 1;
+                let value = self.sum()?;
+                if self.peek() != Some(b')') {
+                    return Err(self.unexpected());
+                }
+                self.pos += 1;
+                Ok(value)
+            }
+            Some(c) if c.is_ascii_digit() => self.number(),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn number(&mut self) -> Result<u64, String> {
+        let rest = &self.src[self.pos..];
+        let (radix, prefix_len) = match rest {
+            [b'0', b'x' | b'X', ..] => (16, 2),
+            [b'0', b'b' | b'B', ..] => (2, 2),
+            [b'0', b'o' | b'O', ..] => (8, 2),
+            _ => (10, 0),
+        };
+        let start = self.pos;
+        self.pos += prefix_len;
+
+        let mut value: u64 = 0;
+        let mut digits = 0;
+        while let Some(&c) = self.src.get(self.pos) {
+            let digit = match c {
+                b'_' => {
+                    self.pos += 1;
+                    continue;
+                }
+                _ => match (c as char).to_digit(radix) {
+                    Some(d) => d,
+                    None => break,
+                },
+            };
+            value = value
+                .checked_mul(radix as u64)
+                .and_then(|v| v.checked_add(digit as u64))
+                .ok_or("number exceeds 64 bits")?;
+            digits += 1;
+            self.pos += 1;
+        }
+        let text = String::from_utf8_lossy(&self.src[start..self.pos]).to_string();
+        if digits == 0 {
+            return Err(format!("'{text}' has no digits"));
+        }
+
+        // size suffix: K, Ki, KiB (powers of 1024)
+        let shift = match self.src.get(self.pos).map(u8::to_ascii_uppercase) {
+            Some(b'K') => 10,
+            Some(b'M') => 20,
+            Some(b'G') => 30,
+            Some(b'T') => 40,
+            _ => 0,
+        };
+        if shift != 0 {
+            self.pos += 1;
+            if self.src.get(self.pos) == Some(&b'i') {
+                self.pos += 1;
+            }
+            if let Some(b'B' | b'b') = self.src.get(self.pos) {
+                self.pos += 1;
+            }
+            value = value
+                .checked_mul(1 << shift)
+                .ok_or("number exceeds 64 bits")?;
+        }
+
+        if let Some(&c) = self.src.get(self.pos)
+            && c.is_ascii_alphanumeric()
+        {
+            return Err(format!(
+                "invalid digit '{}' in number '{text}' (base {radix})",
+                c as char
+            ));
+        }
+        Ok(value)
+    }
+}
+
+// evaluates an address expression
+pub fn parse_expr(src: &str) -> Result<u64, String> {
+    let mut parser = ExprParser {
+        src: src.as_bytes(),
+        pos: 0,
+    };
+    let value = parser.sum()?;
+    if parser.peek().is_some() {
+        return Err(parser.unexpected());
+    }
+    Ok(value)
+}
+
+// address of -s and -e, resolved against the file by Dump::check_args
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Addr {
+    Absolute(u64), // 0x100
+    FromEnd(u64),  // -0x100: bytes back from the end of the file
+    Relative(u64), // +64: bytes after the first address (-s) or after -s (-e)
+    Bit(u64),      // 13bit: bit 13 counted from address 0, bit 5 of the byte at 1 (--bits)
+}
+
+// an expression with a bit suffix (eg: 13bit, 0x10bits) counts bits instead of bytes
+fn strip_bits(src: &str) -> Option<&str> {
+    src.strip_suffix("bits").or_else(|| src.strip_suffix("bit"))
+}
+
+fn parse_addr(src: &str) -> Result<Addr, String> {
+    let src = src.trim();
+    if let Some(expr) = strip_bits(src) {
+        if src.starts_with(['-', '+']) {
+            return Err("bit positions can't be relative (eg: 13bit)".to_string());
+        }
+        parse_expr(expr).map(Addr::Bit)
+    } else if let Some(expr) = src.strip_prefix('-') {
+        parse_expr(expr).map(Addr::FromEnd)
+    } else if let Some(expr) = src.strip_prefix('+') {
+        parse_expr(expr).map(Addr::Relative)
     } else {
-        src.parse::<u64>().map_err(|e| e.to_string())
+        parse_expr(src).map(Addr::Absolute)
+    }
+}
+
+// number of -n
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Len {
+    Bytes(u64), // 0x100
+    Bits(u64),  // 11bit (--bits)
+}
+
+fn parse_len(src: &str) -> Result<Len, String> {
+    let src = src.trim();
+    match strip_bits(src) {
+        Some(expr) => parse_expr(expr).map(Len::Bits),
+        None => parse_expr(src).map(Len::Bytes),
+    }
+}
+
+// range of -r, start:end (end exclusive, start: to the end of the file) or start+length
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RangeArg {
+    pub start: u64,
+    pub end: Option<u64>,
+}
+
+fn parse_range(src: &str) -> Result<RangeArg, String> {
+    if let Some((start, end)) = src.split_once(':') {
+        let start = parse_expr(start)?;
+        let end = match end.trim() {
+            "" => None,
+            end => Some(parse_expr(end)?),
+        };
+        return Ok(RangeArg { start, end });
+    }
+
+    // the length follows the last + outside of parentheses
+    let mut depth = 0;
+    let mut plus = None;
+    for (i, c) in src.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            '+' if depth == 0 => plus = Some(i),
+            _ => {}
+        }
+    }
+    let Some(plus) = plus else {
+        return Err("expected start:end or start+length".to_string());
+    };
+    let start = parse_expr(&src[..plus])?;
+    let end = start
+        .checked_add(parse_expr(&src[plus + 1..])?)
+        .ok_or("result exceeds 64 bits")?;
+    Ok(RangeArg {
+        start,
+        end: Some(end),
+    })
+}
+
+// size of --split, it can't be 0
+fn parse_size(src: &str) -> Result<u64, String> {
+    match parse_expr(src)? {
+        0 => Err("size must be at least 1 byte".to_string()),
+        size => Ok(size),
     }
 }
 
 #[derive(Parser, Debug)]
 #[command(version)]
 pub struct Cli {
-    /// Path to file to dump
-    #[arg()]
-    pub file: String,
+    /// Paths of the files to dump (- reads stdin), globs are expanded
+    #[arg(required_unless_present = "pid")]
+    pub files: Vec<String>,
+
+    /// Dump the memory of the running process with this id (linux)
+    #[arg(
+        short = 'p',
+        long,
+        conflicts_with_all = ["files", "input", "decompress", "recursive", "ranges", "head", "tail", "follow", "base_addr"]
+    )]
+    pub pid: Option<u32>,
+
+    /// Memory mapping of --pid to dump (eg: heap, stack, libc)
+    #[arg(short = 'm', long, requires = "pid")]
+    pub map: Option<String>,
+
+    /// Dump the files inside of directories and their subdirectories
+    #[arg(short = 'R', long)]
+    pub recursive: bool,
+
+    /// Input format
+    #[arg(short, long)]
+    pub input: Option<String>,
+
+    /// Decompress the file (format is detected if omitted)
+    #[arg(short = 'z', long, num_args = 0..=1, default_missing_value = "auto")]
+    pub decompress: Option<String>,
 
     /// Visualization mode
     #[arg(short, long)]
     pub visualization: Option<String>,
 
-    /// Start address of dump
-    #[arg(short, long, value_parser = parse_int)]
-    pub start: Option<u64>,
+    /// Start address of dump (-N: N bytes before the end of the file, Nbit: bit N with --bits)
+    #[arg(short, long, value_parser = parse_addr, allow_hyphen_values = true)]
+    pub start: Option<Addr>,
 
-    /// Number of bytes to show
-    #[arg(short, long, value_parser = parse_int)]
-    pub num_bytes: Option<u64>,
+    /// Number of bytes to show (Nbit: N bits with --bits)
+    #[arg(short, long, value_parser = parse_len)]
+    pub num_bytes: Option<Len>,
 
-    /// End address of dump
-    #[arg(short, long, value_parser = parse_int)]
-    pub end: Option<u64>,
+    /// End address of dump (-N: N bytes before the end of the file, +N: N bytes after -s)
+    #[arg(short, long, value_parser = parse_addr, allow_hyphen_values = true)]
+    pub end: Option<Addr>,
+
+    /// Range to dump (start:end or start+length), can be repeated to dump several ranges
+    #[arg(
+        short,
+        long = "range",
+        value_parser = parse_range,
+        conflicts_with_all = ["start", "num_bytes", "end"]
+    )]
+    pub ranges: Vec<RangeArg>,
+
+    /// Maximum number of bytes dumped of every file
+    #[arg(long, value_parser = parse_expr)]
+    pub max_per_file: Option<u64>,
+
+    /// Dump the first N bytes (only they are read)
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = parse_expr,
+        conflicts_with_all = ["input", "decompress", "start", "num_bytes", "end", "ranges", "tail"]
+    )]
+    pub head: Option<u64>,
+
+    /// Dump the last N bytes (stdin is passed through a buffer of N bytes)
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = parse_expr,
+        conflicts_with_all = ["input", "decompress", "start", "num_bytes", "end", "ranges"]
+    )]
+    pub tail: Option<u64>,
+
+    /// Dump the last rows (default: 10) and keep dumping the bytes appended to the file
+    #[arg(
+        short = 'F',
+        long,
+        value_name = "ROWS",
+        num_args = 0..=1,
+        default_missing_value = "10",
+        value_parser = parse_expr,
+        conflicts_with_all = ["input", "decompress", "start", "num_bytes", "end", "ranges", "format", "max_per_file", "head", "tail", "hash", "block_hash"]
+    )]
+    pub follow: Option<u64>,
+
+    /// Address shown for the first byte of the file, -s and -e use the shifted addresses
+    #[arg(long, value_parser = parse_expr)]
+    pub base_addr: Option<u64>,
+
+    /// Output format
+    #[arg(short, long)]
+    pub format: Option<String>,
+
+    /// Encoding of the text panel (ascii, utf8, utf16le, utf16be, latin1, cp437, ebcdic,
+    /// shiftjis)
+    #[arg(long, conflicts_with = "format")]
+    pub encoding: Option<String>,
+
+    /// Show every byte as its 8 bits, the most (msb, default) or least (lsb) significant first
+    #[arg(
+        long,
+        value_name = "ORDER",
+        num_args = 0..=1,
+        default_missing_value = "msb",
+        conflicts_with_all = ["visualization", "format", "follow", "extract", "scan", "block_hash"]
+    )]
+    pub bits: Option<String>,
+
+    /// Print a table of the file formats found in the dumped bytes instead of the dump
+    #[arg(long, conflicts_with_all = ["format", "extract", "follow", "hash", "block_hash", "mark_signatures"])]
+    pub scan: bool,
+
+    /// Mark the file formats found in the dumped bytes (see --scan) in the dump
+    #[arg(long, conflicts_with_all = ["follow", "format"])]
+    pub mark_signatures: bool,
+
+    /// Hash the dumped bytes (crc8, crc16, crc32, adler32, md5, sha1, sha256, xxhash),
+    /// comma separated or repeated
+    #[arg(long, value_delimiter = ',')]
+    pub hash: Vec<String>,
+
+    /// Add a column with the checksum of every block of the dump (algorithms of --hash)
+    #[arg(long, value_name = "HASH")]
+    pub block_hash: Option<String>,
+
+    /// Size of the --block-hash blocks, a multiple of 16 (default: 16, one block per row)
+    #[arg(long, value_parser = parse_expr, requires = "block_hash")]
+    pub block_size: Option<u64>,
+
+    /// Write the dumped bytes raw to FILE (- or omitted: stdout) instead of dumping them
+    #[arg(
+        short = 'x',
+        long,
+        value_name = "FILE",
+        num_args = 0..=1,
+        default_missing_value = "-",
+        conflicts_with_all = ["format", "follow", "hash", "block_hash"]
+    )]
+    pub extract: Option<String>,
+
+    /// Split the extracted bytes into files of SIZE bytes (FILE.000, FILE.001, ...)
+    #[arg(long, value_name = "SIZE", value_parser = parse_size, requires = "extract")]
+    pub split: Option<u64>,
+
+    /// Number of threads formatting the rows (0 or omitted: one per cpu)
+    #[arg(short = 'j', long)]
+    pub threads: Option<usize>,
 }
 
 impl Cli {
@@ -37,3 +419,64 @@ impl Cli {
         Cli::parse()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_expr_test() {
+        assert_eq!(parse_expr("256"), Ok(256));
+        assert_eq!(parse_expr("0x1_000"), Ok(0x1000));
+        assert_eq!(parse_expr("0b1010"), Ok(10));
+        assert_eq!(parse_expr("0o17"), Ok(15));
+        assert_eq!(parse_expr("4K"), Ok(4096));
+        assert_eq!(parse_expr("2Gi"), Ok(2 << 30));
+        assert_eq!(parse_expr("1MiB"), Ok(1 << 20));
+        assert_eq!(parse_expr("0x1000+0x20*3"), Ok(0x1060));
+        assert_eq!(parse_expr("(1 + 2) * 3"), Ok(9));
+    }
+
+    #[test]
+    fn parse_expr_error_test() {
+        assert_eq!(
+            parse_expr("0b102"),
+            Err("invalid digit '2' in number '0b10' (base 2)".to_string())
+        );
+        assert_eq!(parse_expr("0x"), Err("'0x' has no digits".to_string()));
+        assert_eq!(parse_expr("1-2"), Err("result is negative".to_string()));
+        assert_eq!(parse_expr("1/0"), Err("division by zero".to_string()));
+        assert_eq!(
+            parse_expr("(1+2"),
+            Err("unexpected end of expression".to_string())
+        );
+        assert_eq!(
+            parse_expr("1 2"),
+            Err("unexpected '2' at position 3".to_string())
+        );
+        assert!(parse_expr("16E").is_err());
+    }
+
+    #[test]
+    fn parse_addr_test() {
+        assert_eq!(parse_addr("0x100"), Ok(Addr::Absolute(0x100)));
+        assert_eq!(parse_addr("-0x100"), Ok(Addr::FromEnd(0x100)));
+        assert_eq!(parse_addr("+64"), Ok(Addr::Relative(64)));
+        assert_eq!(parse_addr("13bit"), Ok(Addr::Bit(13)));
+        assert_eq!(parse_addr("0x10bits"), Ok(Addr::Bit(16)));
+        assert!(parse_addr("-13bit").is_err());
+        assert_eq!(parse_len("0x1b"), Ok(Len::Bytes(0x1b)));
+        assert_eq!(parse_len("11bit"), Ok(Len::Bits(11)));
+    }
+
+    #[test]
+    fn parse_range_test() {
+        let range = |start, end| Ok(RangeArg { start, end });
+        assert_eq!(parse_range("0x0:0x40"), range(0, Some(0x40)));
+        assert_eq!(parse_range("0x4000:"), range(0x4000, None));
+        assert_eq!(parse_range("0x4000+0x100"), range(0x4000, Some(0x4100)));
+        assert_eq!(parse_range("0x10+0x20+8"), range(0x30, Some(0x38)));
+        assert_eq!(parse_range("(1+2)*16+(2+2)"), range(48, Some(52)));
+        assert!(parse_range("0x4000").is_err());
+    }
+}
@@ -1,5 +1,7 @@
 use crate::dump::VisuMode;
 use colored::*;
+use std::fmt::Write as _;
+use std::io::{self, Write};
 
 #[macro_export]
 macro_rules! println_error {
@@ -33,189 +35,382 @@ pub fn enable_ansi_support() {
     }
 }
 
-// Frame printing functions
+// Frame writing functions
 const FRAME_R: u8 = 120;
 const FRAME_G: u8 = 120;
 const FRAME_B: u8 = 120;
 
-pub fn print_frame_head(left_base_padding: usize, right_base_padding: usize) {
+// the title and width of an extra column right of the ASCII column (eg: --block-hash)
+pub type FrameColumn<'a> = Option<(&'a str, usize)>;
+
+// title is the header of the text panel (ASCII or the name of the --encoding)
+pub fn write_frame_head(
+    w: &mut dyn Write,
+    left_base_padding: usize,
+    right_base_padding: usize,
+    title: &str,
+    column: FrameColumn,
+) -> io::Result<()> {
     // first line
-    // print left corner
-    print!("{}", format!("┌").truecolor(FRAME_R, FRAME_G, FRAME_B));
+    // write left corner
+    write!(w, "{}", "┌".truecolor(FRAME_R, FRAME_G, FRAME_B))?;
 
-    // print the left padding
+    // write the left padding
     for _i in 0..left_base_padding {
-        print!("{}", format!("─").truecolor(FRAME_R, FRAME_G, FRAME_B));
+        write!(w, "{}", "─".truecolor(FRAME_R, FRAME_G, FRAME_B))?;
     }
 
-    // print the BASE stroke
-    print!("{}", format!("────").truecolor(FRAME_R, FRAME_G, FRAME_B));
+    // write the BASE stroke
+    write!(w, "{}", "────".truecolor(FRAME_R, FRAME_G, FRAME_B))?;
 
-    // print the right padding
+    // write the right padding
     for _i in 0..right_base_padding {
-        print!("{}", format!("─").truecolor(FRAME_R, FRAME_G, FRAME_B));
+        write!(w, "{}", "─".truecolor(FRAME_R, FRAME_G, FRAME_B))?;
     }
 
-    // print the rest of the frame
-    println!(
+    // write the rest of the frame
+    write!(
+        w,
         "{}",
-        format!(
-            "┬────────────────────────────────┬────────────────────────────────┬──────────────────┐"
-        )
-        .truecolor(FRAME_R, FRAME_G, FRAME_B)
-    );
+        "┬────────────────────────────────┬────────────────────────────────┬──────────────────"
+            .truecolor(FRAME_R, FRAME_G, FRAME_B)
+    )?;
+    write_column_border(w, "┬", "┐", column)?;
 
     // middle line
-    // print left wall
-    print!("{}", format!("│").truecolor(FRAME_R, FRAME_G, FRAME_B));
+    // write left wall
+    write!(w, "{}", "│".truecolor(FRAME_R, FRAME_G, FRAME_B))?;
 
-    // print left padding
+    // write left padding
     for _i in 0..left_base_padding {
-        print!(" ");
+        write!(w, " ")?;
     }
 
-    // print BASE
-    print!("BASE");
+    // write BASE
+    write!(w, "BASE")?;
 
-    // print right padding
+    // write right padding
     for _i in 0..right_base_padding {
-        print!(" ");
+        write!(w, " ")?;
     }
 
-    // print wall with one space
-    print!("{}", format!("│ ").truecolor(FRAME_R, FRAME_G, FRAME_B));
+    // write wall with one space
+    write!(w, "{}", "│ ".truecolor(FRAME_R, FRAME_G, FRAME_B))?;
 
-    // print index
+    // write index
     for i in 0..16 {
-        print!("{}", format!("{:02X} ", i).cyan());
+        write!(w, "{}", format!("{:02X} ", i).cyan())?;
         if i == 7 {
-            print!("{} ", format!("│").truecolor(FRAME_R, FRAME_G, FRAME_B));
+            write!(w, "{} ", "│".truecolor(FRAME_R, FRAME_G, FRAME_B))?;
         } else if i != 15 {
-            print!(" ");
+            write!(w, " ")?;
         }
     }
 
-    // print ASCII section
-    println!(
-        "{}      ASCII       {}",
-        format!("│").truecolor(FRAME_R, FRAME_G, FRAME_B),
-        format!("│").truecolor(FRAME_R, FRAME_G, FRAME_B)
-    );
+    // write the text section
+    write!(
+        w,
+        "{} {title:^16} ",
+        "│".truecolor(FRAME_R, FRAME_G, FRAME_B)
+    )?;
+    if let Some((title, width)) = column {
+        write!(
+            w,
+            "{} {title:^width$} ",
+            "│".truecolor(FRAME_R, FRAME_G, FRAME_B)
+        )?;
+    }
+    writeln!(w, "{}", "│".truecolor(FRAME_R, FRAME_G, FRAME_B))?;
 
     // bottom line
-    // print wall
-    print!("{}", format!("├").truecolor(FRAME_R, FRAME_G, FRAME_B));
+    // write wall
+    write!(w, "{}", "├".truecolor(FRAME_R, FRAME_G, FRAME_B))?;
 
-    // print the left padding
+    // write the left padding
     for _i in 0..left_base_padding {
-        print!("{}", format!("─").truecolor(FRAME_R, FRAME_G, FRAME_B));
+        write!(w, "{}", "─".truecolor(FRAME_R, FRAME_G, FRAME_B))?;
     }
 
-    // print the BASE stroke
-    print!("{}", format!("────").truecolor(FRAME_R, FRAME_G, FRAME_B));
+    // write the BASE stroke
+    write!(w, "{}", "────".truecolor(FRAME_R, FRAME_G, FRAME_B))?;
 
-    // print the right padding
+    // write the right padding
     for _i in 0..right_base_padding {
-        print!("{}", format!("─").truecolor(FRAME_R, FRAME_G, FRAME_B));
+        write!(w, "{}", "─".truecolor(FRAME_R, FRAME_G, FRAME_B))?;
     }
 
-    // print the rest of the frame
-    println!(
+    // write the rest of the frame
+    write!(
+        w,
         "{}",
-        format!(
-            "┼────────────────────────────────┼────────────────────────────────┼──────────────────┤"
-        )
-        .truecolor(FRAME_R, FRAME_G, FRAME_B)
-    );
+        "┼────────────────────────────────┼────────────────────────────────┼──────────────────"
+            .truecolor(FRAME_R, FRAME_G, FRAME_B)
+    )?;
+    write_column_border(w, "┼", "┤", column)
 }
 
-pub fn print_frame_foot(left_base_padding: usize, right_base_padding: usize) {
-    // print left corner
-    print!("{}", format!("└").truecolor(FRAME_R, FRAME_G, FRAME_B));
+pub fn write_frame_foot(
+    w: &mut dyn Write,
+    left_base_padding: usize,
+    right_base_padding: usize,
+    column: FrameColumn,
+) -> io::Result<()> {
+    // write left corner
+    write!(w, "{}", "└".truecolor(FRAME_R, FRAME_G, FRAME_B))?;
 
-    // print the left padding
+    // write the left padding
     for _i in 0..left_base_padding {
-        print!("{}", format!("─").truecolor(FRAME_R, FRAME_G, FRAME_B));
+        write!(w, "{}", "─".truecolor(FRAME_R, FRAME_G, FRAME_B))?;
     }
 
-    // print the BASE stroke
-    print!("{}", format!("────").truecolor(FRAME_R, FRAME_G, FRAME_B));
+    // write the BASE stroke
+    write!(w, "{}", "────".truecolor(FRAME_R, FRAME_G, FRAME_B))?;
 
-    // print the right padding
+    // write the right padding
     for _i in 0..right_base_padding {
-        print!("{}", format!("─").truecolor(FRAME_R, FRAME_G, FRAME_B));
+        write!(w, "{}", "─".truecolor(FRAME_R, FRAME_G, FRAME_B))?;
     }
 
-    // print the rest of the frame
-    println!(
+    // write the rest of the frame
+    write!(
+        w,
         "{}",
-        format!(
-            "┴────────────────────────────────┴────────────────────────────────┴──────────────────┘"
-        )
-        .truecolor(FRAME_R, FRAME_G, FRAME_B)
-    );
+        "┴────────────────────────────────┴────────────────────────────────┴──────────────────"
+            .truecolor(FRAME_R, FRAME_G, FRAME_B)
+    )?;
+    write_column_border(w, "┴", "┘", column)
 }
 
-pub fn print_frame_part(str: impl AsRef<str>) {
-    print!(
+// writes the border of the extra column (if any) and the corner that ends a border line
+fn write_column_border(
+    w: &mut dyn Write,
+    joint: &str,
+    corner: &str,
+    column: FrameColumn,
+) -> io::Result<()> {
+    let mut border = String::new();
+    if let Some((_, width)) = column {
+        border = format!("{joint}{}", "─".repeat(width + 2));
+    }
+    writeln!(
+        w,
         "{}",
-        format!("{}", str.as_ref()).truecolor(FRAME_R, FRAME_G, FRAME_B)
-    );
+        format!("{border}{corner}").truecolor(FRAME_R, FRAME_G, FRAME_B)
+    )
 }
 
-pub fn print_base_addr(addr: u64, base_width: usize) {
-    print!(
-        "{}",
-        format!("{addr:0width$X}", width = base_width).yellow()
-    );
+// width of the byte columns and of the ASCII column of the bit view (--bits)
+const BITS_COLUMN_WIDTH: usize = 37;
+const BITS_ASCII_WIDTH: usize = 10;
+
+// a border line of the bit view from the BASE column to the right corner
+fn bits_border(base_width: usize, left: &str, joint: &str, corner: &str) -> ColoredString {
+    format!(
+        "{left}{}{joint}{}{joint}{}{joint}{}{corner}",
+        "─".repeat(base_width),
+        "─".repeat(BITS_COLUMN_WIDTH),
+        "─".repeat(BITS_COLUMN_WIDTH),
+        "─".repeat(BITS_ASCII_WIDTH)
+    )
+    .truecolor(FRAME_R, FRAME_G, FRAME_B)
 }
 
-pub fn print_repeated(str: impl AsRef<str>, n: usize) {
-    print!("{}", format!("{}", str.as_ref()).repeat(n));
+// the frame head of the bit view (--bits): the offsets of the 8 bytes of a line and the
+// indices of their bits (in the shown order) below them
+pub fn write_bits_frame_head(
+    w: &mut dyn Write,
+    left_base_padding: usize,
+    right_base_padding: usize,
+    bit_indices: &str,
+    title: &str,
+) -> io::Result<()> {
+    let base_width = left_base_padding + 4 + right_base_padding;
+    let wall = "│".truecolor(FRAME_R, FRAME_G, FRAME_B);
+    writeln!(w, "{}", bits_border(base_width, "┌", "┬", "┐"))?;
+
+    // byte offsets
+    write!(
+        w,
+        "{wall}{}BASE{}{wall}",
+        " ".repeat(left_base_padding),
+        " ".repeat(right_base_padding)
+    )?;
+    for i in 0..8 {
+        write!(w, " {}", format!("{:^8}", format!("{i:02X}")).cyan())?;
+        if i == 3 || i == 7 {
+            write!(w, " {wall}")?;
+        }
+    }
+    writeln!(w, "{title:^BITS_ASCII_WIDTH$}{wall}")?;
+
+    // bit indices
+    write!(w, "{wall}{}{wall}", " ".repeat(base_width))?;
+    for i in 0..8 {
+        write!(w, " {}", bit_indices.cyan())?;
+        if i == 3 || i == 7 {
+            write!(w, " {wall}")?;
+        }
+    }
+    writeln!(w, "{}{wall}", " ".repeat(BITS_ASCII_WIDTH))?;
+
+    writeln!(w, "{}", bits_border(base_width, "├", "┼", "┤"))
 }
 
-pub fn print_dark(str: impl AsRef<str>) {
-    const DARK_R: u8 = 64;
-    const DARK_G: u8 = 64;
-    const DARK_B: u8 = 64;
-    print!(
-        "{}",
-        format!("{}", str.as_ref()).truecolor(DARK_R, DARK_G, DARK_B)
-    );
+pub fn write_bits_frame_foot(
+    w: &mut dyn Write,
+    left_base_padding: usize,
+    right_base_padding: usize,
+) -> io::Result<()> {
+    let base_width = left_base_padding + 4 + right_base_padding;
+    writeln!(w, "{}", bits_border(base_width, "└", "┴", "┘"))
 }
 
-pub fn print_byte(data: u8, visu_mode: VisuMode) {
+// returns the color data is highlighted with in visu_mode (None if it is drawn plain)
+pub fn byte_color(data: u8, visu_mode: VisuMode) -> Option<Color> {
     match visu_mode {
-        VisuMode::Default => {
-            print!("{data:02X} ");
-        }
+        VisuMode::Default => None,
         VisuMode::AsciiGraphic => {
             if data.is_ascii_graphic() || data == b' ' {
-                print!("{} ", format!("{data:02X}").bright_green());
+                Some(Color::BrightGreen)
             } else {
-                print!("{} ", format!("{data:02X}").bright_red());
+                Some(Color::BrightRed)
             }
         }
-        VisuMode::HighlightZeros => {
-            if data == 0 {
-                print!("{} ", format!("{data:02X}").bright_magenta());
-            } else {
-                print!("{data:02X} ");
-            }
+        VisuMode::HighlightZeros => (data == 0).then_some(Color::BrightMagenta),
+        VisuMode::HighBytes => (data >= 0x80).then_some(Color::BrightBlue),
+        VisuMode::ControlChars => data.is_ascii_control().then_some(Color::Yellow),
+    }
+}
+
+// the escape codes colored wraps around text of the given style, as (prefix, suffix)
+fn escapes(styled: ColoredString) -> (String, String) {
+    let text = styled.to_string();
+    match text.split_once('\0') {
+        Some((prefix, suffix)) => (prefix.to_string(), suffix.to_string()),
+        None => (String::new(), String::new()),
+    }
+}
+
+// the text without the ANSI escape codes of colored, tests compare the text without them (the
+// colors depend on the environment and on the global override of colored)
+#[cfg(test)]
+pub fn strip_escapes(text: &str) -> String {
+    let mut plain = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // ESC [ parameters and a final byte from @ to ~
+            chars.next();
+            chars.find(|c| ('@'..='~').contains(c));
+        } else {
+            plain.push(c);
         }
-        VisuMode::HighBytes => {
-            if data >= 0x80 {
-                print!("{} ", format!("{data:02X}").bright_blue());
-            } else {
-                print!("{data:02X} ");
-            }
+    }
+    plain
+}
+
+// Row building blocks of the dump, every cell is formatted (and colored) once per dump
+// instead of once per byte, rows are then assembled by copying the cells
+pub struct RowPalette {
+    frame: (String, String),
+    wall: String, // "│ " frame part between the columns
+    addr: (String, String),
+    hex: Vec<String>,    // "XX " cell of every byte value
+    ascii: Vec<String>,  // ascii cell of every byte value
+    marked: Vec<String>, // hex cell of every byte value inside of a marked signature
+    unset: String,       // hex cell of a byte that isn't set (gap in the address space)
+    set_bit: String,     // a 1 of the bit view (--bits)
+    dark: (String, String),
+}
+
+impl RowPalette {
+    pub fn new(visu_mode: VisuMode) -> RowPalette {
+        const DARK_R: u8 = 64;
+        const DARK_G: u8 = 64;
+        const DARK_B: u8 = 64;
+        let dark = escapes("\0".truecolor(DARK_R, DARK_G, DARK_B));
+
+        let hex = (0..=255u8)
+            .map(|data| match byte_color(data, visu_mode) {
+                Some(color) => format!("{} ", format!("{data:02X}").color(color)),
+                None => format!("{data:02X} "),
+            })
+            .collect();
+        let ascii = (0..=255u8)
+            .map(|data| {
+                if data.is_ascii_graphic() || data == b' ' {
+                    (data as char).to_string()
+                } else {
+                    format!("{}.{}", dark.0, dark.1)
+                }
+            })
+            .collect();
+
+        let marked = (0..=255u8)
+            .map(|data| format!("{} ", format!("{data:02X}").black().on_bright_cyan()))
+            .collect();
+
+        let frame = escapes("\0".truecolor(FRAME_R, FRAME_G, FRAME_B));
+        RowPalette {
+            wall: format!("{}│ {}", frame.0, frame.1),
+            frame,
+            addr: escapes("\0".yellow()),
+            hex,
+            ascii,
+            marked,
+            unset: format!("{}--{} ", dark.0, dark.1),
+            set_bit: "1".bright_green().to_string(),
+            dark,
         }
-        VisuMode::ControlChars => {
-            if data.is_ascii_control() {
-                print!("{} ", format!("{data:02X}").yellow());
-            } else {
-                print!("{data:02X} ");
-            }
+    }
+
+    pub fn frame_part(&self, row: &mut String, str: &str) {
+        row.push_str(&self.frame.0);
+        row.push_str(str);
+        row.push_str(&self.frame.1);
+    }
+
+    pub fn wall(&self, row: &mut String) {
+        row.push_str(&self.wall);
+    }
+
+    pub fn base_addr(&self, row: &mut String, addr: u64, base_width: usize) {
+        row.push_str(&self.addr.0);
+        // writing into a String can't fail
+        let _ = write!(row, "{addr:0width$X}", width = base_width);
+        row.push_str(&self.addr.1);
+    }
+
+    pub fn dark(&self, row: &mut String, str: &str) {
+        row.push_str(&self.dark.0);
+        row.push_str(str);
+        row.push_str(&self.dark.1);
+    }
+
+    pub fn hex(&self, row: &mut String, data: Option<u8>) {
+        match data {
+            Some(data) => row.push_str(&self.hex[data as usize]),
+            None => row.push_str(&self.unset),
+        }
+    }
+
+    pub fn marked_hex(&self, row: &mut String, data: u8) {
+        row.push_str(&self.marked[data as usize]);
+    }
+
+    // a bit of the bit view, None if it lies outside of the bit range
+    pub fn bit(&self, row: &mut String, set: Option<bool>) {
+        match set {
+            Some(true) => row.push_str(&self.set_bit),
+            Some(false) => row.push('0'),
+            None => self.dark(row, "·"),
+        }
+    }
+
+    pub fn ascii(&self, row: &mut String, data: Option<u8>) {
+        match data {
+            Some(data) => row.push_str(&self.ascii[data as usize]),
+            None => row.push(' '),
         }
     }
 }
@@ -1,7 +1,43 @@
 use crate::Cli;
-#[cfg(windows)]
+use crate::Error;
+use crate::args::{Addr, Len, RangeArg};
 use crate::color::*;
+pub use bits::BitOrder;
+use decompress::Compression;
+pub use encoding::Encoding;
+pub use hash::HashAlgo;
+use process::Process;
+use scan::Hit;
+use sparse::Sparse;
+use std::convert::Infallible;
 use std::fs;
+use std::io::{self, BufWriter, IsTerminal, Read, Write};
+use std::ops::{Bound, Range, RangeBounds, RangeInclusive};
+use std::thread;
+
+mod bits;
+mod decode;
+mod decompress;
+mod encoding;
+mod extract;
+mod follow;
+mod hash;
+mod html;
+mod image;
+mod json;
+mod markdown;
+mod process;
+mod records;
+mod scan;
+mod sparse;
+mod styled;
+mod window;
+
+// rows a thread formats at once
+const ROWS_PER_CHUNK: u64 = 4096;
+
+// width of the text of separator rows, from the hex column to the right wall
+const NOTE_TEXT_WIDTH: usize = 83;
 
 #[derive(Debug)]
 enum AddrMode {
@@ -12,6 +48,34 @@ enum AddrMode {
     NE,      // n val -e val args were passed
     N,       // only -n val arg was passed
     E,       // only -e val arg was passed
+    R,       // -r val args were passed
+    Head,    // --head val arg was passed
+    Tail,    // --tail val arg was passed
+}
+
+impl AddrMode {
+    fn name(&self) -> &'static str {
+        match self {
+            AddrMode::Default => "default",
+            AddrMode::S => "s",
+            AddrMode::SN => "sn",
+            AddrMode::SE => "se",
+            AddrMode::NE => "ne",
+            AddrMode::N => "n",
+            AddrMode::E => "e",
+            AddrMode::R => "r",
+            AddrMode::Head => "head",
+            AddrMode::Tail => "tail",
+        }
+    }
+}
+
+// size of the input the dump was read from
+#[derive(Debug, Clone, Copy)]
+enum InputSize {
+    Data,       // the whole input was read into data
+    Total(u64), // only a part of the input was read (--head, --tail)
+    Unknown,    // stdin was only read up to --head
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -23,171 +87,466 @@ pub enum VisuMode {
     ControlChars,   // -v control arg was passed
 }
 
+#[derive(Debug, Clone, Copy)]
+pub enum InputFormat {
+    Auto,      // no -i format arg was passed
+    Raw,       // -i raw  arg was passed
+    IntelHex,  // -i ihex arg was passed
+    SRecord,   // -i srec arg was passed
+    Base64,    // -i base64  arg was passed
+    Base32,    // -i base32  arg was passed
+    Ascii85,   // -i ascii85 arg was passed
+    HexString, // -i hex     arg was passed
+    Escaped,   // -i escaped arg was passed
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum OutputFormat {
+    Default,       // no -f format arg was passed
+    Json,          // -f json     arg was passed
+    JsonLines,     // -f jsonl    arg was passed
+    Html,          // -f html     arg was passed
+    Svg,           // -f svg      arg was passed
+    Png,           // -f png      arg was passed
+    Markdown,      // -f md       arg was passed
+    MarkdownTable, // -f md-table arg was passed
+    IntelHex,      // -f ihex     arg was passed
+    SRecord,       // -f srec     arg was passed
+}
+
 pub struct Dump {
+    path: Option<String>, // None if the dump wasn't read from a file
+    show_path: bool,      // print a File: line above the header
     data: Vec<u8>,
+    gaps: Vec<(Range<u64>, u64)>, // unset parts (offsets, not in data), gap bytes before each
+    base_addr: u64,               // address of the first byte of data
+    compression: Option<(Compression, u64)>, // format and size of compressed input
+    data_offset: u64,             // offset of data in the input (--tail)
+    input_size: InputSize,
+    process: Option<Process>, // the process the memory was read from (--pid)
+    sparse: Option<Sparse>,   // block device or sparse file, only read in the dump range
+    holes: Vec<(Range<u64>, u64)>, // holes of a sparse file (not in data), hole bytes before each
     filesize: u64,
-    file_max_index: u64,
+    file_end: u64, // address after the last byte of data
     start_addr: u64,
-    end_addr: u64,
-    num_bytes: u64,
+    num_bytes: u64,         // the dump range is start_addr..start_addr + num_bytes
+    skips: Vec<Range<u64>>, // parts of the dump range between the -r ranges, sorted
     addr_mode: AddrMode,
     visu_mode: VisuMode,
+    output_format: OutputFormat,
+    threads: usize,                      // threads formatting the rows, 0: one per cpu
+    hashes: Vec<HashAlgo>,               // hashes of the dump range shown in the header
+    block_hash: Option<(HashAlgo, u64)>, // checksum column of the blocks of this size
+    marks: Vec<Hit>, // signatures marked in the dump (--mark-signatures), sorted
+    bit_order: Option<BitOrder>, // bit view (--bits)
+    bit_range: Option<Range<u64>>, // bits shown by the bit view (bit granular -s, -n, -e)
+    encoding: Encoding, // the text panel decodes the bytes with it
+}
+
+// the gaps (sorted offsets) with the gap bytes before each of them
+fn gap_layout(gaps: Vec<Range<u64>>) -> Vec<(Range<u64>, u64)> {
+    let mut before = 0;
+    gaps.into_iter()
+        .map(|gap| {
+            let entry = (gap.clone(), before);
+            before += gap.end - gap.start;
+            entry
+        })
+        .collect()
 }
 
 impl Dump {
-    pub fn new(path: &String) -> Dump {
-        let data = match fs::read(path) {
-            Ok(d) => d,
-            Err(e) => {
-                println_error!("Error: {}", e);
-                std::process::exit(1);
-            }
+    /// Loads the file at path ("-" for stdin) in the input format. Block devices and sparse
+    /// files are only read in the dump range by [`Dump::load`].
+    pub fn new(
+        path: &String,
+        input: &Option<String>,
+        decompress: &Option<String>,
+    ) -> Result<Dump, Error> {
+        let input_format = match input {
+            Some(i) => match i.to_lowercase().as_str() {
+                "auto" => InputFormat::Auto,
+                "raw" => InputFormat::Raw,
+                "ihex" => InputFormat::IntelHex,
+                "srec" => InputFormat::SRecord,
+                "base64" => InputFormat::Base64,
+                "base32" => InputFormat::Base32,
+                "ascii85" => InputFormat::Ascii85,
+                "hex" => InputFormat::HexString,
+                "escaped" => InputFormat::Escaped,
+                _ => return Err(Error::UnknownInputFormat(i.clone())),
+            },
+            None => InputFormat::Auto,
         };
 
-        let filesize: u64 = if data.len() != 0 {
-            data.len() as u64
+        // block devices and sparse files are read once the dump range is known
+        if path != "-"
+            && decompress.is_none()
+            && let InputFormat::Auto | InputFormat::Raw = input_format
+            && let Some((size, device)) = sparse::probe(path).map_err(|source| Error::Read {
+                path: path.clone(),
+                source,
+            })?
+        {
+            return Ok(Dump::from_sparse(path, size, device));
+        }
+
+        // read the file (- reads stdin)
+        let read = if path == "-" {
+            let mut stdin_data = Vec::new();
+            std::io::stdin()
+                .read_to_end(&mut stdin_data)
+                .map(|_| stdin_data)
         } else {
-            println_error!("Error: Cannot dump file with 0 bytes");
-            std::process::exit(1);
+            fs::read(path)
         };
+        let mut data = read.map_err(|source| Error::Read {
+            path: path.clone(),
+            source,
+        })?;
 
-        let file_max_index: u64 = filesize - 1;
+        // decode text encoded input
+        let decoder: Option<(&str, decode::Decoder)> = match input_format {
+            InputFormat::Base64 => Some(("base64", decode::base64)),
+            InputFormat::Base32 => Some(("base32", decode::base32)),
+            InputFormat::Ascii85 => Some(("ascii85", decode::ascii85)),
+            InputFormat::HexString => Some(("hex", decode::hex_string)),
+            InputFormat::Escaped => Some(("escaped", decode::escaped)),
+            _ => None,
+        };
+        if let Some((name, decoder)) = decoder {
+            data = decoder(&String::from_utf8_lossy(&data)).map_err(|message| Error::Decode {
+                format: name,
+                message,
+            })?;
+        }
+
+        // decompress the input (-z without format detects it)
+        let compression_format = match decompress.as_ref().map(|z| z.to_lowercase()) {
+            Some(z) if z == "auto" => Compression::detect(&data),
+            Some(z) if z == "gzip" => Some(Compression::Gzip),
+            Some(z) if z == "zstd" => Some(Compression::Zstd),
+            Some(z) if z == "xz" => Some(Compression::Xz),
+            Some(z) if z == "bzip2" => Some(Compression::Bzip2),
+            Some(z) => return Err(Error::UnknownCompression(z)),
+            None => None,
+        };
+        let mut compression = None;
+        if let Some(format) = compression_format {
+            let compressed_size = data.len() as u64;
+            data = format
+                .decompress(&data)
+                .map_err(|message| Error::Decompress {
+                    format: format.name(),
+                    message,
+                })?;
+            compression = Some((format, compressed_size));
+        }
+
+        // load record files into their address space
+        let chunks = match input_format {
+            InputFormat::Auto => records::detect(&data),
+            InputFormat::IntelHex | InputFormat::SRecord => {
+                let text = String::from_utf8_lossy(&data);
+                let parsed = match input_format {
+                    InputFormat::IntelHex => records::parse_ihex(&text),
+                    _ => records::parse_srec(&text),
+                };
+                Some(parsed.map_err(Error::Records)?)
+            }
+            _ => None,
+        };
+        let mut base_addr = 0;
+        let mut gaps = Vec::new();
+        if let Some(chunks) = chunks {
+            (base_addr, data, gaps) = records::flatten(&chunks);
+        }
 
         // enable ansi support on windows (for colorful console output)
         #[cfg(windows)]
         enable_ansi_support();
 
+        let mut dump = Dump::from_layout(data, base_addr, gaps);
+        dump.path = Some(path.clone());
+        dump.compression = compression;
+        Ok(dump)
+    }
+
+    /// Creates a dump of the given bytes, by default all bytes are dumped with the default
+    /// visualization in the framed text format.
+    ///
+    /// ```
+    /// use hexdump::{Dump, VisuMode};
+    ///
+    /// let mut out = Vec::new();
+    /// Dump::from_bytes(b"Hello, World!".to_vec())
+    ///     .range(0..5)
+    ///     .mode(VisuMode::AsciiGraphic)
+    ///     .write_to(&mut out)
+    ///     .unwrap();
+    /// ```
+    pub fn from_bytes(data: impl Into<Vec<u8>>) -> Dump {
+        Dump::from_layout(data.into(), 0, Vec::new())
+    }
+
+    // dump that starts at base_addr and has the unset parts gaps (sorted offsets), data only
+    // holds the set bytes
+    fn from_layout(data: Vec<u8>, base_addr: u64, gaps: Vec<Range<u64>>) -> Dump {
+        let gaps = gap_layout(gaps);
+        let filesize = data.len() as u64
+            + gaps
+                .last()
+                .map_or(0, |(gap, before)| before + gap.end - gap.start);
+
         Dump {
+            path: None,
+            show_path: false,
             data,
+            gaps,
+            base_addr,
+            compression: None,
+            data_offset: 0,
+            input_size: InputSize::Data,
+            process: None,
+            sparse: None,
+            holes: Vec::new(),
             filesize,
-            file_max_index,
-            start_addr: 0,
+            file_end: base_addr + filesize,
+            start_addr: base_addr,
             num_bytes: filesize,
-            end_addr: file_max_index,
+            skips: Vec::new(),
             addr_mode: AddrMode::Default,
             visu_mode: VisuMode::Default,
+            output_format: OutputFormat::Default,
+            threads: 1,
+            hashes: Vec::new(),
+            block_hash: None,
+            marks: Vec::new(),
+            bit_order: None,
+            bit_range: None,
+            encoding: Encoding::Ascii,
         }
     }
 
-    pub fn check_args(&mut self, args: &Cli) {
+    /// Limits the dump to the addresses of range, an open bound goes to the first/last byte.
+    /// Ranges that leave the data are reported by [`Dump::write_to`].
+    pub fn range(mut self, range: impl RangeBounds<u64>) -> Dump {
+        let start = match range.start_bound() {
+            Bound::Included(&s) => s,
+            Bound::Excluded(&s) => s.saturating_add(1),
+            Bound::Unbounded => self.base_addr,
+        };
+        // exclusive end of the range
+        let end = match range.end_bound() {
+            Bound::Included(&e) => e.saturating_add(1),
+            Bound::Excluded(&e) => e,
+            Bound::Unbounded => self.file_end,
+        };
+
+        self.start_addr = start;
+        self.num_bytes = end.saturating_sub(start);
+        self.skips.clear();
+        self.addr_mode = AddrMode::SE;
+        self
+    }
+
+    /// Limits the dump to several ranges (overlapping ones are merged), they are shown in one
+    /// frame where a separator row replaces the rows between them.
+    ///
+    /// ```
+    /// use hexdump::Dump;
+    ///
+    /// let mut out = Vec::new();
+    /// Dump::from_bytes(vec![0; 0x200])
+    ///     .ranges([0x0..0x40, 0x100..0x140])
+    ///     .write_to(&mut out)
+    ///     .unwrap();
+    /// ```
+    pub fn ranges(mut self, ranges: impl IntoIterator<Item = Range<u64>>) -> Dump {
+        self.set_ranges(ranges.into_iter().collect());
+        self
+    }
+
+    /// Shows the first byte of the data at base_addr (eg: the load address of a firmware image),
+    /// ranges use the shifted addresses. Data that doesn't fit below 2^64 is reported by
+    /// [`Dump::write_to`].
+    pub fn base_addr(mut self, base_addr: u64) -> Dump {
+        self.rebase(base_addr);
+        self
+    }
+
+    /// Prints a `File:` line with the path above the header (eg: when several files are
+    /// dumped one after another).
+    pub fn show_path(mut self, show_path: bool) -> Dump {
+        self.show_path = show_path;
+        self
+    }
+
+    /// Sets the visualization that highlights the bytes.
+    pub fn mode(mut self, visu_mode: VisuMode) -> Dump {
+        self.visu_mode = visu_mode;
+        self
+    }
+
+    /// Sets the output format the dump is written in.
+    pub fn format(mut self, output_format: OutputFormat) -> Dump {
+        self.output_format = output_format;
+        self
+    }
+
+    /// Sets the number of threads that format the rows of the text dump (0: one per cpu).
+    /// The output is the same for any number of threads.
+    pub fn threads(mut self, threads: usize) -> Dump {
+        self.threads = threads;
+        self
+    }
+
+    /// Shows the hashes of the dumped bytes below the `Dumping` line of the header.
+    pub fn hashes(mut self, algos: impl IntoIterator<Item = HashAlgo>) -> Dump {
+        self.hashes = algos.into_iter().collect();
+        self
+    }
+
+    /// Adds a column with the checksum of every block of block_size bytes (the blocks are
+    /// aligned to the addresses like the rows) to the dump. Writing the dump fails with
+    /// [`Error::InvalidBlockSize`] if block_size isn't a multiple of 16.
+    pub fn block_hash(mut self, algo: HashAlgo, block_size: u64) -> Dump {
+        self.block_hash = Some((algo, block_size));
+        self
+    }
+
+    /// Reads the dump range of inputs that are only read in the range they are dumped in (the
+    /// memory of [`Dump::process`], block devices and sparse files). Call it once the range is
+    /// set, writing such a dump before fails. It does nothing for all other inputs.
+    pub fn load(&mut self) -> Result<(), Error> {
+        self.load_process()?;
+        self.load_sparse()
+    }
+
+    pub fn check_args(&mut self, args: &Cli) -> Result<(), Error> {
+        // Shift the addresses before -s and -e are resolved against them
+        if let Some(b) = args.base_addr {
+            self.rebase(b);
+            self.check_base()?;
+        }
+
+        // Resolve the address expressions of -s and -e (+N after -e is relative to -s)
+        let start = args
+            .start
+            .map(|s| self.resolve_addr("-s", s, self.base_addr))
+            .transpose()?;
+        let end = args
+            .end
+            .map(|e| self.resolve_addr("-e", e, start.unwrap_or(self.base_addr)))
+            .transpose()?;
+
+        // Bit granular -s, -n and -e (13bit) dump the bytes the bits lie in
+        let start_bit = match args.start {
+            Some(Addr::Bit(bit)) => Some(bit),
+            _ => None,
+        };
+        let end_bit = match args.end {
+            Some(Addr::Bit(bit)) => Some(bit),
+            _ => None,
+        };
+        // -n in bits fixes the bit range, it starts at -s or ends at -e (in bits or bytes)
+        let num_bits_range = match args.num_bytes {
+            Some(Len::Bits(n)) => {
+                let start_bit = start_bit.or(start.map(|s| s.saturating_mul(8)));
+                let end_bit = end_bit
+                    .map(|e| e + 1)
+                    .or(end.map(|e| e.saturating_add(1).saturating_mul(8)));
+                Some(match (start_bit, end_bit) {
+                    (Some(s), _) => s..s.saturating_add(n),
+                    (None, Some(e)) => match e.checked_sub(n) {
+                        Some(s) if s >= self.base_addr.saturating_mul(8) => s..e,
+                        _ => {
+                            return Err(Error::NumExceedsEnd {
+                                num: n.div_ceil(8),
+                                end: end.unwrap_or_default(),
+                            });
+                        }
+                    },
+                    _ => {
+                        let s = self.start_addr.saturating_mul(8);
+                        s..s.saturating_add(n)
+                    }
+                })
+            }
+            _ => None,
+        };
+        // the dumped bytes are the bytes the bit range lies in
+        let num_bytes = args.num_bytes.map(|n| match (n, &num_bits_range) {
+            (Len::Bits(_), Some(bits)) => bits.end.div_ceil(8) - bits.start / 8,
+            (Len::Bytes(n), _) | (Len::Bits(n), None) => n,
+        });
+
+        // Check that -s and -e don't lie before the first address
+        if let Some(s) = start
+            && s < self.base_addr
+        {
+            return Err(Error::StartBeforeBase {
+                start: s,
+                base: self.base_addr,
+            });
+        }
+        if let Some(e) = end
+            && e < self.base_addr
+        {
+            return Err(Error::EndBeforeBase {
+                end: e,
+                base: self.base_addr,
+            });
+        }
+
         // Check correct usage of -s, -n, -e
-        match (args.start, args.num_bytes, args.end) {
+        match (start, num_bytes, end) {
             // only passed -s val
             (Some(s), None, None) => {
                 // check if:
                 // start_addr <= file_max_index
                 // else: throw error
-                if s < self.filesize {
-                    self.start_addr = s;
-                    self.num_bytes = self.filesize - s;
-                    self.addr_mode = AddrMode::S;
-                } else {
-                    println_error!(
-                        "Error: start_addr (-s {}) exceeds file_max_index ({})",
-                        s,
-                        self.file_max_index
-                    );
-                    std::process::exit(1);
-                }
+                self.check_start(s)?;
+                self.start_addr = s;
+                self.num_bytes = self.file_end - s;
+                self.addr_mode = AddrMode::S;
             }
             // only passed -n val
             (None, Some(n), None) => {
                 // check if:
                 // num_bytes <= filesize
-                // num_bytes != 0
-                // else: throw according error
-                if n <= self.filesize && n != 0 {
-                    self.num_bytes = n;
-                    self.end_addr = n - 1;
-                    self.addr_mode = AddrMode::N;
-                } else {
-                    if n > self.filesize {
-                        println_error!(
-                            "Error: num_bytes (-n {}) exceeds filesize ({})",
-                            n,
-                            self.filesize
-                        );
-                    } else {
-                        println_error!(
-                            "Error: num_bytes (-n {}) can't be 0 (can't show 0 bytes)",
-                            n
-                        );
-                    }
-                    std::process::exit(1);
-                }
+                // else: throw error
+                self.check_num(n)?;
+                self.num_bytes = n;
+                self.addr_mode = AddrMode::N;
             }
             // only passed -e val
             (None, None, Some(e)) => {
                 // check if:
                 // end_addr <= file_max_index
                 // else: throw error
-                if e <= self.file_max_index {
-                    self.end_addr = e;
-                    self.num_bytes = e + 1;
-                    self.addr_mode = AddrMode::E;
-                } else {
-                    println_error!(
-                        "Error: end_addr (-e {}) exceeds file_max_index ({})",
-                        e,
-                        self.file_max_index
-                    );
-                    std::process::exit(1);
-                }
+                self.check_end(e)?;
+                self.num_bytes = e - self.base_addr + 1;
+                self.addr_mode = AddrMode::E;
             }
             // -s val -n val args were passed
             (Some(s), Some(n), None) => {
                 // check if:
                 // start_addr <= file_max_index
                 // num_bytes <= than filesize
-                // calculated end_addr (start_addr + num_bytes) <= filesize
-                // num_byes != 0 (can't show 0 bytes)
+                // calculated end_addr (start_addr + num_bytes - 1) <= file_max_index
                 // else: throw according errors
-                if s <= self.file_max_index
-                    && n <= self.filesize - s
-                    && s + n <= self.filesize
-                    && n != 0
-                {
-                    self.start_addr = s;
-                    self.num_bytes = n;
-                    self.end_addr = s + n - 1;
-                    self.addr_mode = AddrMode::SN;
-                } else {
-                    if s > self.file_max_index {
-                        println_error!(
-                            "Error: start_addr (-s {}) exceeds file_max_index ({})",
-                            s,
-                            self.file_max_index
-                        );
-                        println_info!("Consider: reduce -s to be in file range\n");
-                    }
-                    if n > self.filesize {
-                        println_error!(
-                            "Error: num_bytes (-n {}) exceeds filesize ({})",
-                            n,
-                            self.filesize
-                        );
-                        println_info!("Consider: reduce -n to be in file range\n");
-                    }
-                    if s + n > self.filesize && n != 0 {
-                        let e = s + (n - 1);
-                        println_error!(
-                            "Error: calculated end_addr ({}) exceeds file_max_index ({})",
-                            e,
-                            self.file_max_index
-                        );
-                        println_info!(
-                            "Consider: reduce -n to be in file range or drop -n arg to dump file from -s to eof\n"
-                        );
-                    }
-                    if n == 0 {
-                        println_error!(
-                            "Error: num_bytes (-n {}) can't be 0 (can't show 0 bytes)\n",
-                            n
-                        );
-                    }
-
-                    std::process::exit(1);
+                self.check_start(s)?;
+                self.check_num(n)?;
+                if n > self.file_end - s {
+                    return Err(Error::CalcEndExceedsMax {
+                        end: s.saturating_add(n - 1),
+                        max: self.file_end - 1,
+                    });
                 }
+                self.start_addr = s;
+                self.num_bytes = n;
+                self.addr_mode = AddrMode::SN;
             }
             // -s val -e val args were passed
             (Some(s), None, Some(e)) => {
@@ -196,165 +555,641 @@ impl Dump {
                 // end_addr <= file_max_index
                 // start_addr <= end_endr
                 // else: throw according error
-                if s <= self.file_max_index && e <= self.file_max_index && s <= e {
-                    self.start_addr = s;
-                    self.end_addr = e;
-                    self.num_bytes = (e - s) + 1;
-                    self.addr_mode = AddrMode::SE;
-                } else {
-                    if s > self.file_max_index {
-                        println_error!(
-                            "Error: start_addr (-s {}) exceeds file_max_index ({})",
-                            s,
-                            self.file_max_index
-                        );
-                        println_info!("Consider: reduce -s to be in file range\n");
-                    }
-                    if e > self.file_max_index {
-                        println_error!(
-                            "Error: end_addr (-e {}) exceeds file_max_index ({})",
-                            e,
-                            self.file_max_index
-                        );
-                        println_info!("Consider: reduce -e to be in file range\n");
-                    }
-                    if s > e {
-                        println_error!(
-                            "Error: start_addr (-s {}) is bigger than end_addr (-e {})",
-                            s,
-                            e
-                        );
-                        println_info!("Consider: reduce -s to be <= -e\n");
-                    }
-
-                    std::process::exit(1);
+                self.check_start(s)?;
+                self.check_end(e)?;
+                if s > e {
+                    return Err(Error::StartAfterEnd { start: s, end: e });
                 }
+                self.start_addr = s;
+                self.num_bytes = (e - s) + 1;
+                self.addr_mode = AddrMode::SE;
             }
             // -n val -e val args were passed
             (None, Some(n), Some(e)) => {
                 // check if:
                 // end_addr <= file_max_index
                 // num_bytes <= filesize
-                // num_bytes != 0
-                // calculated start_addr <= file_max_index
-                // calculated start_addr >= 0
+                // calculated start_addr >= first address
                 // else: throw according error
-
+                self.check_end(e)?;
+                self.check_num(n)?;
                 // avoid arithmetic_overflow error if n > e
-                let mut s: u64 = 0;
-                let mut s_valid: bool = false;
-                if n <= e + 1 {
-                    s = e - (n - 1);
-                    s_valid = true;
-                }
-
-                if e <= self.file_max_index
-                    && n <= self.filesize
-                    && n != 0
-                    && s <= self.file_max_index
-                    && s_valid
-                {
-                    self.start_addr = s;
-                    self.num_bytes = n;
-                    self.end_addr = e;
-                    self.addr_mode = AddrMode::NE;
-                } else {
-                    if e > self.file_max_index {
-                        println_error!(
-                            "Error: end_addr (-e {}) exceeds file_max_index ({})",
-                            e,
-                            self.file_max_index
-                        );
-                        println_info!("Consider: reduce -e to be in file range\n");
-                    }
-                    if n > self.filesize {
-                        println_error!(
-                            "Error: num_bytes (-n {}) exceeds filesize ({})",
-                            n,
-                            self.filesize
-                        );
-                        println_info!("Consider: reduce -n to be in file range\n");
-                    }
-                    if n == 0 {
-                        println_error!(
-                            "Error: num_bytes (-n {}) can't be 0 (can't show 0 bytes)\n",
-                            n
-                        );
-                    }
-                    if s_valid && s > self.file_max_index {
-                        println_error!(
-                            "Error: calculated start_addr ({}) exceeds file_max_index ({})",
-                            s,
-                            self.file_max_index
-                        );
-                        println_info!("Consider: reduce -e to be in file range\n");
-                    }
-                    if !s_valid {
-                        println_error!(
-                            "Error: num_bytes (-n {}) is bigger than end_addr (-e {})",
-                            n,
-                            e
-                        );
-                        println_info!("Consider: reduce -n to be <= -e + 1\n");
-                    }
-                    std::process::exit(1);
+                if n > e - self.base_addr + 1 {
+                    return Err(Error::NumExceedsEnd { num: n, end: e });
                 }
+                // -n 0 is an empty range right after -e
+                self.start_addr = e + 1 - n;
+                self.num_bytes = n;
+                self.addr_mode = AddrMode::NE;
             }
             // no -s val, -n val, -e val args were passed (do nothing, use vals from new())
             (None, None, None) => {}
             // ERROR: all -s val, -n val, -e val args were passed (invalid use of args)
-            _ => {
-                println_error!("Error: invalid use of args (-s, -n, -e)");
-                println_info!("----------------------Usage----------------------");
-                println_info!("-s:    Prints data from address -s to end of file");
-                println_info!("-n:    Prints -n bytes starting from address 0");
-                println_info!("-e:    Prints data from address 0 to address -e");
-                println_info!("-s -n: Prints -n bytes starting from address -s");
-                println_info!("-s -e: Prints data from address -s to address -e");
-                println_info!("-n -e: Prints -n bytes back from address -e");
-                println_error!("-s -n -e: INVALID COMBINATION");
-                std::process::exit(1);
-            }
+            _ => return Err(Error::InvalidArgCombination),
         };
 
+        // handle -r args (clap rejects them together with -s, -n, -e)
+        if !args.ranges.is_empty() {
+            let ranges = args
+                .ranges
+                .iter()
+                .map(|r| self.resolve_range(r))
+                .collect::<Result<_, _>>()?;
+            self.set_ranges(ranges);
+        }
+
+        // handle --max-per-file args
+        if let Some(max) = args.max_per_file {
+            self.limit(max);
+        }
+
         // handle -v args
-        match &args.visualization {
-            Some(v) => {
-                let arg = v.to_lowercase();
-                match arg.as_str() {
-                    "ascii" => {
-                        self.visu_mode = VisuMode::AsciiGraphic;
-                    }
-                    "zeros" => {
-                        self.visu_mode = VisuMode::HighlightZeros;
-                    }
-                    "high" => {
-                        self.visu_mode = VisuMode::HighBytes;
-                    }
-                    "control" => {
-                        self.visu_mode = VisuMode::ControlChars;
+        if let Some(v) = &args.visualization {
+            let arg = v.to_lowercase();
+            match arg.as_str() {
+                "ascii" => {
+                    self.visu_mode = VisuMode::AsciiGraphic;
+                }
+                "zeros" => {
+                    self.visu_mode = VisuMode::HighlightZeros;
+                }
+                "high" => {
+                    self.visu_mode = VisuMode::HighBytes;
+                }
+                "control" => {
+                    self.visu_mode = VisuMode::ControlChars;
+                }
+                _ => return Err(Error::UnknownVisualization(v.clone())),
+            };
+        }
+
+        // handle --bits args, bit granular -s, -n and -e limit the bits that are shown
+        if let Some(b) = &args.bits {
+            let order = BitOrder::parse(b).ok_or_else(|| Error::UnknownBitOrder(b.clone()))?;
+            self.bit_order = Some(order);
+        }
+        if (start_bit.is_some() || end_bit.is_some() || num_bits_range.is_some())
+            && self.bit_order.is_none()
+        {
+            return Err(Error::BitRangeNeedsBits);
+        }
+        if let Some(bits) = num_bits_range {
+            self.bit_range = Some(bits);
+        } else if start_bit.is_some() || end_bit.is_some() {
+            let bytes_end = (self.start_addr + self.num_bytes).saturating_mul(8);
+            let end_bit = end_bit.map_or(bytes_end, |e| e + 1);
+            let start_bit = start_bit.unwrap_or(self.start_addr.saturating_mul(8));
+            self.bit_range = Some(start_bit..end_bit.max(start_bit));
+        }
+
+        // handle --encoding args
+        if let Some(e) = &args.encoding {
+            self.encoding = Encoding::parse(e).ok_or_else(|| Error::UnknownEncoding(e.clone()))?;
+        }
+
+        // handle --hash args
+        self.hashes = args
+            .hash
+            .iter()
+            .map(|h| HashAlgo::parse(h).ok_or_else(|| Error::UnknownHash(h.clone())))
+            .collect::<Result<_, _>>()?;
+
+        // handle --block-hash args (one block per row by default)
+        if let Some(h) = &args.block_hash {
+            let algo = HashAlgo::parse(h).ok_or_else(|| Error::UnknownHash(h.clone()))?;
+            self.block_hash = Some((algo, args.block_size.unwrap_or(16)));
+            self.check_block_size()?;
+        }
+
+        // handle -j args (no -j arg: one thread per cpu)
+        self.threads = args.threads.unwrap_or(0);
+
+        // handle -f args
+        if let Some(f) = &args.format {
+            let arg = f.to_lowercase();
+            match arg.as_str() {
+                "json" => {
+                    self.output_format = OutputFormat::Json;
+                }
+                "jsonl" => {
+                    self.output_format = OutputFormat::JsonLines;
+                }
+                "html" => {
+                    self.output_format = OutputFormat::Html;
+                }
+                "svg" => {
+                    self.output_format = OutputFormat::Svg;
+                }
+                "png" => {
+                    self.output_format = OutputFormat::Png;
+                }
+                "md" => {
+                    self.output_format = OutputFormat::Markdown;
+                }
+                "md-table" => {
+                    self.output_format = OutputFormat::MarkdownTable;
+                }
+                "ihex" => {
+                    self.output_format = OutputFormat::IntelHex;
+                }
+                "srec" => {
+                    self.output_format = OutputFormat::SRecord;
+                }
+                _ => return Err(Error::UnknownOutputFormat(f.clone())),
+            };
+        }
+
+        // read the memory of the resolved range (--pid, block devices, sparse files)
+        self.load()?;
+
+        // handle --mark-signatures args
+        if args.mark_signatures {
+            self.marks = self.scan_hits();
+        }
+        Ok(())
+    }
+
+    // range of an -r arg, it has to lie inside of the data
+    fn resolve_range(&self, range: &RangeArg) -> Result<Range<u64>, Error> {
+        let end = range.end.unwrap_or(self.file_end);
+        if range.start > end {
+            return Err(Error::RangeReversed {
+                start: range.start,
+                end,
+            });
+        }
+        if range.start < self.base_addr || end > self.file_end {
+            return Err(Error::RangeExceedsData {
+                start: range.start,
+                end,
+                data_start: self.base_addr,
+                data_end: self.file_end,
+            });
+        }
+        Ok(range.start..end)
+    }
+
+    // dumps the union of ranges, the dump range spans all of them and skips the parts between
+    fn set_ranges(&mut self, mut ranges: Vec<Range<u64>>) {
+        ranges.retain(|r| !r.is_empty());
+        ranges.sort_by_key(|r| r.start);
+        let mut merged: Vec<Range<u64>> = Vec::new();
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+
+        let (start, end) = match (merged.first(), merged.last()) {
+            (Some(first), Some(last)) => (first.start, last.end),
+            _ => (self.base_addr, self.base_addr),
+        };
+        self.start_addr = start;
+        self.num_bytes = end - start;
+        self.skips = merged.windows(2).map(|w| w[0].end..w[1].start).collect();
+        self.addr_mode = AddrMode::R;
+    }
+
+    // cuts the dump after its first max dumped bytes
+    fn limit(&mut self, max: u64) {
+        let mut left = max;
+        for range in self.dumped_ranges() {
+            let len = range.end - range.start;
+            if len >= left {
+                let cut = range.start + left;
+                self.num_bytes = cut - self.start_addr;
+                self.skips.retain(|skip| skip.start < cut);
+                return;
+            }
+            left -= len;
+        }
+    }
+
+    // checks that inputs which are only read in the dump range were read
+    fn check_loaded(&self) -> Result<(), Error> {
+        if self.process.as_ref().is_some_and(|p| !p.loaded) {
+            return Err(Error::ProcessNotLoaded);
+        }
+        if self.sparse.as_ref().is_some_and(|s| !s.loaded) {
+            return Err(Error::NotLoaded);
+        }
+        Ok(())
+    }
+
+    // moves the first byte of the input to base_addr, the default range moves along
+    fn rebase(&mut self, base_addr: u64) {
+        let base_addr = base_addr.saturating_add(self.data_offset);
+        self.base_addr = base_addr;
+        self.file_end = base_addr.saturating_add(self.filesize);
+        if let AddrMode::Default | AddrMode::Head | AddrMode::Tail = self.addr_mode {
+            self.start_addr = base_addr;
+        }
+    }
+
+    // checks that the data fits into the 64 bit address space after base_addr
+    fn check_base(&self) -> Result<(), Error> {
+        if self.file_end - self.base_addr < self.filesize {
+            return Err(Error::BaseAddrOverflow {
+                base: self.base_addr,
+                filesize: self.filesize,
+            });
+        }
+        Ok(())
+    }
+
+    // address of an -s or -e arg, relative addresses count from relative_to
+    fn resolve_addr(&self, arg: &'static str, addr: Addr, relative_to: u64) -> Result<u64, Error> {
+        match addr {
+            Addr::Absolute(a) => Ok(a),
+            Addr::FromEnd(n) => self
+                .file_end
+                .checked_sub(n)
+                .filter(|a| *a >= self.base_addr)
+                .ok_or(Error::FromEndExceedsFilesize {
+                    arg,
+                    back: n,
+                    filesize: self.filesize,
+                }),
+            Addr::Relative(n) => Ok(relative_to.saturating_add(n)),
+            Addr::Bit(bit) => Ok(bit / 8),
+        }
+    }
+
+    fn check_start(&self, s: u64) -> Result<(), Error> {
+        if self.filesize == 0 {
+            return Err(Error::EmptyFile);
+        }
+        if s >= self.file_end {
+            return Err(Error::StartExceedsMax {
+                start: s,
+                max: self.file_end - 1,
+            });
+        }
+        Ok(())
+    }
+
+    fn check_end(&self, e: u64) -> Result<(), Error> {
+        if self.filesize == 0 {
+            return Err(Error::EmptyFile);
+        }
+        if e >= self.file_end {
+            return Err(Error::EndExceedsMax {
+                end: e,
+                max: self.file_end - 1,
+            });
+        }
+        Ok(())
+    }
+
+    fn check_num(&self, n: u64) -> Result<(), Error> {
+        if n > self.filesize {
+            return Err(Error::NumExceedsFilesize {
+                num: n,
+                filesize: self.filesize,
+            });
+        }
+        Ok(())
+    }
+
+    // line numbers (addr / 16) of the rows of the dump, empty if no bytes are dumped
+    fn rows(&self) -> Range<u64> {
+        match self.last_addr() {
+            Some(last) => self.start_addr / 16..last / 16 + 1,
+            None => 0..0,
+        }
+    }
+
+    // address of the last dumped byte, None if no bytes are dumped
+    fn last_addr(&self) -> Option<u64> {
+        self.num_bytes.checked_sub(1).map(|n| self.start_addr + n)
+    }
+
+    fn in_range(&self, addr: u64) -> bool {
+        addr >= self.start_addr && addr - self.start_addr < self.num_bytes && !self.skipped(addr)
+    }
+
+    // returns if addr lies between two -r ranges
+    fn skipped(&self, addr: u64) -> bool {
+        let i = self.skips.partition_point(|skip| skip.end <= addr);
+        self.skips.get(i).is_some_and(|skip| skip.start <= addr)
+    }
+
+    // the ranges of the dump (one range unless -r skips parts of it)
+    fn dumped_ranges(&self) -> Vec<Range<u64>> {
+        let mut ranges = Vec::new();
+        let mut start = self.start_addr;
+        for skip in &self.skips {
+            ranges.push(start..skip.start);
+            start = skip.end;
+        }
+        ranges.push(start..self.start_addr + self.num_bytes);
+        ranges
+    }
+
+    /// Returns the number of bytes the dump shows.
+    pub fn dumped_bytes(&self) -> u64 {
+        let skipped: u64 = self.skips.iter().map(|skip| skip.end - skip.start).sum();
+        self.num_bytes - skipped
+    }
+
+    // runs of rows (line numbers) that are dumped, each with the note row shown above it: the
+    // separator of the skip or hole that replaced the rows before it (skips that don't cover a
+    // whole row are left blank), the memory mapping (--pid) or the signature
+    // (--mark-signatures) that starts in its first row
+    fn row_runs(&self) -> Vec<(Option<String>, Range<u64>)> {
+        let rows = self.rows();
+        let hex_width = self.hex_width();
+
+        // row the previous run ends at, row the next run starts at and the note between them
+        let mut breaks: Vec<(u64, u64, String)> = Vec::new();
+        for skip in &self.skips {
+            let (skip_first, skip_end) = (skip.start.div_ceil(16), skip.end / 16);
+            if skip_first < skip_end {
+                breaks.push((skip_first, skip_end, Self::skip_text(skip, hex_width)));
+            }
+        }
+        for (hole, _) in &self.holes {
+            let (hole_first, hole_end) = (hole.start.div_ceil(16), hole.end / 16);
+            if hole_first < hole_end {
+                breaks.push((hole_first, hole_end, Self::hole_text(hole, hex_width)));
+            }
+        }
+        if let Some(process) = &self.process {
+            let end = self.start_addr + self.num_bytes;
+            for mapping in &process.mappings {
+                if mapping.range.start < end && mapping.range.end > self.start_addr {
+                    let row = mapping.range.start.max(self.start_addr) / 16;
+                    breaks.push((row, row, mapping.note(hex_width)));
+                }
+            }
+        }
+        for hit in &self.marks {
+            let row = hit.addr / 16;
+            breaks.push((row, row, Self::hit_text(hit, hex_width)));
+        }
+        breaks.sort_by_key(|(row, _, _)| *row);
+
+        let mut runs = Vec::new();
+        let (mut note, mut first) = (None, rows.start);
+        for (end, next, text) in breaks {
+            runs.push((note, first..end));
+            (note, first) = (Some(text), next);
+        }
+        runs.push((note, first..rows.end));
+        runs
+    }
+
+    // text cut to the width of a note row (the end of a long path is more telling)
+    fn fit_note(text: &str, width: usize) -> String {
+        let len = text.chars().count();
+        if len <= width {
+            return text.to_string();
+        }
+        let tail: String = text.chars().skip(len - width + 1).collect();
+        format!("…{tail}")
+    }
+
+    // the text of the separator row that replaces the rows of skip
+    fn skip_text(skip: &Range<u64>, hex_width: usize) -> String {
+        format!(
+            "{} bytes skipped ({:0width$X} to {:0width$X})",
+            skip.end - skip.start,
+            skip.start,
+            skip.end - 1,
+            width = hex_width
+        )
+    }
+
+    // width of the addresses in the dump
+    fn hex_width(&self) -> usize {
+        Self::calc_hex_width(self.last_addr().unwrap_or(self.start_addr))
+    }
+
+    // the Dumping line of the dump header
+    fn dumping_line(&self, hex_width: usize) -> String {
+        match self.last_addr() {
+            Some(last) if !self.skips.is_empty() => format!(
+                "Dumping {} bytes in {} ranges from {:0width$X} to {:0width$X}",
+                self.dumped_bytes(),
+                self.skips.len() + 1,
+                self.start_addr,
+                last,
+                width = hex_width
+            ),
+            Some(last) => format!(
+                "Dumping {} bytes from {:0width$X} to {:0width$X}",
+                self.num_bytes,
+                self.start_addr,
+                last,
+                width = hex_width
+            ),
+            None => format!(
+                "Dumping 0 bytes at {:0width$X}",
+                self.start_addr,
+                width = hex_width
+            ),
+        }
+    }
+
+    // returns the ranges of the bytes of the row at base that lie inside of the dump range
+    // (more than one if a skip ends inside of the row)
+    fn row_segments(&self, base: u64) -> Vec<RangeInclusive<u64>> {
+        let last = self.last_addr().unwrap_or(self.start_addr);
+        let range = base.max(self.start_addr)..=(base + 15).min(last);
+        if self.skips.is_empty() {
+            return vec![range];
+        }
+
+        let mut segments: Vec<RangeInclusive<u64>> = Vec::new();
+        for addr in range.filter(|&addr| self.in_range(addr)) {
+            match segments.last_mut() {
+                Some(segment) if *segment.end() + 1 == addr => {
+                    *segment = *segment.start()..=addr;
+                }
+                _ => segments.push(addr..=addr),
+            }
+        }
+        segments
+    }
+
+    // the parts of all dumped rows, in order
+    fn row_parts(&self) -> impl Iterator<Item = RangeInclusive<u64>> + '_ {
+        self.row_runs()
+            .into_iter()
+            .flat_map(|(_, rows)| rows)
+            .flat_map(|line| self.row_segments(line * 16))
+    }
+
+    // returns the byte at addr, None if addr lies in a gap of the address space
+    fn byte(&self, addr: u64) -> Option<u8> {
+        let mut offset = addr - self.base_addr;
+        if !self.holes.is_empty() {
+            // holes read as zeros and aren't part of data
+            let i = self.holes.partition_point(|(hole, _)| hole.end <= addr);
+            if let Some((hole, _)) = self.holes.get(i)
+                && hole.start <= addr
+            {
+                return Some(0);
+            }
+            if let Some((hole, before)) = i.checked_sub(1).map(|i| &self.holes[i]) {
+                offset -= before + (hole.end - hole.start);
+            }
+        }
+        let i = self.gaps.partition_point(|(gap, _)| gap.end <= offset);
+        match self.gaps.get(i) {
+            Some((gap, _)) if gap.start <= offset => None,
+            _ => Some(self.data[(offset - self.gap_bytes_before(i)) as usize]),
+        }
+    }
+
+    // bytes of the gaps before the gap at index i (they aren't in data)
+    fn gap_bytes_before(&self, i: usize) -> u64 {
+        i.checked_sub(1).map_or(0, |i| {
+            self.gaps[i].1 + self.gaps[i].0.end - self.gaps[i].0.start
+        })
+    }
+
+    // for_each_slice with an f that can't fail
+    fn visit_slices(&self, range: Range<u64>, gap_fill: Option<u8>, f: &mut dyn FnMut(&[u8])) {
+        let Ok(()) = self.for_each_slice::<Infallible>(range, gap_fill, &mut |slice| {
+            f(slice);
+            Ok(())
+        });
+    }
+
+    // passes the bytes of range to f in slices, holes as zeros and the bytes in gaps as
+    // gap_fill (left out if None)
+    fn for_each_slice<E>(
+        &self,
+        range: Range<u64>,
+        gap_fill: Option<u8>,
+        f: &mut dyn FnMut(&[u8]) -> Result<(), E>,
+    ) -> Result<(), E> {
+        const SLICE_LEN: usize = 1 << 16;
+        if !self.gaps.is_empty() {
+            let fill = [gap_fill.unwrap_or(0); SLICE_LEN];
+            let mut addr = range.start;
+            while addr < range.end {
+                let offset = addr - self.base_addr;
+                let i = self.gaps.partition_point(|(gap, _)| gap.end <= offset);
+                match self.gaps.get(i) {
+                    Some((gap, _)) if gap.start <= offset => {
+                        let gap_end = (self.base_addr + gap.end).min(range.end);
+                        if gap_fill.is_some() {
+                            for start in (addr..gap_end).step_by(SLICE_LEN) {
+                                let len = (gap_end - start).min(SLICE_LEN as u64);
+                                f(&fill[..len as usize])?;
+                            }
+                        }
+                        addr = gap_end;
                     }
-                    _ => {
-                        println_error!("Error: unknown visualization (-v {v} does not exist)");
-                        println_info!("-------------------------Usage--------------------------");
-                        println_info!("-v ascii:   Highlights ascii printable bytes");
-                        println_info!("-v zeros:   Highlights bytes that have the value 0");
-                        println_info!("-v high:    Highlights bytes that have a value >= 0x80");
-                        println_info!("-v control: Highlights bytes that are control characters");
-                        std::process::exit(1);
+                    next => {
+                        let data_end = next.map_or(range.end, |(gap, _)| {
+                            (self.base_addr + gap.start).min(range.end)
+                        });
+                        let start = (offset - self.gap_bytes_before(i)) as usize;
+                        f(&self.data[start..start + (data_end - addr) as usize])?;
+                        addr = data_end;
                     }
-                };
+                }
+            }
+            return Ok(());
+        }
+
+        // data between the holes
+        let data = |start: u64, end: u64, skipped: u64| {
+            let offset = start - self.base_addr - skipped;
+            &self.data[offset as usize..(offset + end - start) as usize]
+        };
+        let zeros = [0; SLICE_LEN];
+        let mut addr = range.start;
+        let mut skipped = 0; // hole bytes before addr
+        for (hole, before) in &self.holes {
+            if hole.start >= range.end {
+                break;
             }
-            // if no -v arg was passed do nothing
-            None => {}
+            if hole.end > addr {
+                let data_end = hole.start.max(addr);
+                f(data(addr, data_end, skipped))?;
+                let hole_end = hole.end.min(range.end);
+                for start in (data_end..hole_end).step_by(SLICE_LEN) {
+                    let len = (hole_end - start).min(SLICE_LEN as u64);
+                    f(&zeros[..len as usize])?;
+                }
+                addr = hole_end;
+            }
+            skipped = before + (hole.end - hole.start);
+        }
+        if addr < range.end {
+            f(data(addr, range.end, skipped))?;
+        }
+        Ok(())
+    }
+
+    // the File: line above the dump header
+    fn path_line(&self) -> Option<String> {
+        let path = self.path.as_deref().filter(|_| self.show_path)?;
+        Some(format!("File: {path}"))
+    }
+
+    // the Filesize: line of the dump header
+    fn filesize_line(&self) -> String {
+        if let Some(process) = &self.process {
+            return process.header_line();
+        }
+        let Some(filesize) = self.input_filesize() else {
+            return "Filesize: unknown (stdin was only read up to --head)".to_string();
         };
+        match (self.compression, &self.sparse) {
+            (Some((format, compressed_size)), _) => format!(
+                "Filesize: {}, {} compressed: {compressed_size} bytes",
+                Self::format_filesize(filesize),
+                format.name()
+            ),
+            (None, Some(sparse)) if sparse.device => {
+                format!(
+                    "Filesize: {}, block device",
+                    Self::format_filesize(filesize)
+                )
+            }
+            (None, Some(_)) => {
+                let holes: u64 = self.holes.iter().map(|(h, _)| h.end - h.start).sum();
+                let holes = match holes {
+                    0 => String::new(),
+                    holes => format!(" ({holes} bytes of the dump range in holes)"),
+                };
+                format!(
+                    "Filesize: {}, sparse file{holes}",
+                    Self::format_filesize(filesize)
+                )
+            }
+            (None, None) => format!("Filesize: {}", Self::format_filesize(filesize)),
+        }
+    }
+
+    // size of the whole input (bigger than data for --head and --tail), None if unknown
+    fn input_filesize(&self) -> Option<u64> {
+        match self.input_size {
+            InputSize::Data => Some(self.filesize),
+            InputSize::Total(size) => Some(size),
+            InputSize::Unknown => None,
+        }
+    }
+
+    fn ascii_char(byte: u8) -> char {
+        if byte.is_ascii_graphic() || byte == b' ' {
+            byte as char
+        } else {
+            '.'
+        }
     }
 
     fn calc_hex_width(max_num: u64) -> usize {
         let digits = if max_num == 0 {
             1
         } else {
-            ((64 - max_num.leading_zeros() + 3) / 4) as usize
+            (64 - max_num.leading_zeros()).div_ceil(4) as usize
         };
 
         digits.max(4)
@@ -372,6 +1207,9 @@ impl Dump {
             (filesize as f64 / (1024.0 * 1024.0 * 1024.0), "GB")
         };
 
+        if filesize == 0 {
+            return "0 bytes".to_string();
+        }
         let width = Self::calc_hex_width(filesize - 1);
 
         if unit == "bytes" {
@@ -389,70 +1227,253 @@ impl Dump {
         }
     }
 
-    pub fn print_dump(&self) {
-        println!("Filesize: {}", Self::format_filesize(self.filesize));
-        let hex_width = Self::calc_hex_width(self.end_addr);
-        println!(
-            "Dumping {} bytes from {:0width$X} to {:0width$X}",
-            self.num_bytes,
-            self.start_addr,
-            self.end_addr,
-            width = hex_width
-        );
-        println!();
+    // checks that the dump range lies inside of the data
+    fn check_range(&self) -> Result<(), Error> {
+        self.check_loaded()?;
+        self.check_base()?;
+        let end = self.start_addr.saturating_add(self.num_bytes);
+        if self.start_addr < self.base_addr || end > self.file_end {
+            return Err(Error::RangeExceedsData {
+                start: self.start_addr,
+                end,
+                data_start: self.base_addr,
+                data_end: self.file_end,
+            });
+        }
+        Ok(())
+    }
+
+    /// Writes the dump in its output format to w.
+    ///
+    /// Colors are written as ANSI escape codes, they can be turned off with
+    /// `colored::control::set_override(false)`.
+    pub fn write_to(&self, mut w: impl Write) -> Result<(), Error> {
+        self.check_range()?;
+        self.check_block_size()?;
+        let w: &mut dyn Write = &mut w;
+        match self.output_format {
+            OutputFormat::Default => self.write_dump(w)?,
+            OutputFormat::Json => self.write_json(w)?,
+            OutputFormat::JsonLines => self.write_json_lines(w)?,
+            OutputFormat::Html => self.write_html(w)?,
+            OutputFormat::Svg => self.write_svg(w)?,
+            OutputFormat::Png => self.write_png(w)?,
+            OutputFormat::Markdown => self.write_markdown(w)?,
+            OutputFormat::MarkdownTable => self.write_markdown_table(w)?,
+            OutputFormat::IntelHex => self.write_ihex(w)?,
+            OutputFormat::SRecord => self.write_srec(w)?,
+        }
+        Ok(())
+    }
+
+    pub fn print(&self) -> Result<(), Error> {
+        let stdout = io::stdout();
+        if let OutputFormat::Png = self.output_format
+            && stdout.is_terminal()
+        {
+            return Err(Error::PngToTerminal);
+        }
+
+        let mut out = BufWriter::with_capacity(1 << 16, stdout.lock());
+        match self.write_to(&mut out).and_then(|_| Ok(out.flush()?)) {
+            // the reader of the output went away (eg: | head)
+            Err(Error::Write(e)) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            result => result,
+        }
+    }
+
+    fn write_dump(&self, w: &mut dyn Write) -> io::Result<()> {
+        if let Some(path_line) = self.path_line() {
+            writeln!(w, "{path_line}")?;
+        }
+        writeln!(w, "{}", self.filesize_line())?;
+        let hex_width = self.hex_width();
+        writeln!(w, "{}", self.dumping_line(hex_width))?;
+        if let Some(bit_line) = self.bit_line() {
+            writeln!(w, "{bit_line}")?;
+        }
+        for hash_line in self.hash_lines() {
+            writeln!(w, "{hash_line}")?;
+        }
+        writeln!(w)?;
         let left_base_padding = hex_width / 2;
         let right_base_padding = hex_width - left_base_padding;
 
-        // print the dump head
-        print_frame_head(left_base_padding, right_base_padding);
-
-        // print the information
-        let start_base = self.start_addr / 16;
-        let end_base = self.end_addr / 16;
-        for line in start_base..=end_base {
-            let base = line * 16;
-            // print base addr
-            print_frame_part("│");
-            print_repeated(" ", left_base_padding);
-            print_base_addr(base, hex_width);
-            print_repeated(" ", right_base_padding);
-            print_frame_part("│ ");
-
-            // print data
-            for i in 0..16 {
-                let addr = base + i;
-                if addr < self.start_addr || addr > self.end_addr {
-                    print!("   "); // out of range padding
-                } else {
-                    print_byte(self.data[addr as usize], self.visu_mode);
-                }
+        // write the dump head (and the checksum column of --block-hash), the bit view has its
+        // own frame that is 2 columns wider
+        let column_width = self.block_hash_width();
+        let column = self
+            .block_hash
+            .zip(column_width)
+            .map(|((algo, _), width)| (algo.title(), width));
+        let extra_width = match self.bit_order {
+            Some(_) => 2,
+            None => column_width.map_or(0, |width| width + 3),
+        };
+        match self.bit_order {
+            Some(order) => {
+                let indices = order.indices();
+                let title = self.encoding.title();
+                write_bits_frame_head(w, left_base_padding, right_base_padding, indices, title)?
+            }
+            None => {
+                let title = self.encoding.title();
+                write_frame_head(w, left_base_padding, right_base_padding, title, column)?
+            }
+        }
 
-                if i == 7 {
-                    print_frame_part("│ ");
-                } else if i != 15 {
-                    print!(" ");
-                }
+        // write the information, note rows show the bytes skipped between -r ranges and the
+        // memory mappings (--pid)
+        let palette = RowPalette::new(self.visu_mode);
+        for (note, rows) in self.row_runs() {
+            if let Some(note) = note {
+                let mut row = String::new();
+                Self::format_note_row(&palette, &note, hex_width, extra_width, &mut row);
+                w.write_all(row.as_bytes())?;
             }
+            self.write_rows(w, &palette, rows, hex_width)?;
+        }
+        match self.bit_order {
+            Some(_) => write_bits_frame_foot(w, left_base_padding, right_base_padding)?,
+            None => write_frame_foot(w, left_base_padding, right_base_padding, column)?,
+        }
+
+        // an empty line separates the dumps of several files
+        if self.show_path {
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+
+    // writes the rows of lines, every thread formats a chunk of rows into its buffer,
+    // the buffers are then written in order
+    fn write_rows(
+        &self,
+        w: &mut dyn Write,
+        palette: &RowPalette,
+        rows: Range<u64>,
+        hex_width: usize,
+    ) -> io::Result<()> {
+        let threads = match self.threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+        let threads = threads.min((rows.end - rows.start).div_ceil(ROWS_PER_CHUNK) as usize);
+        let mut chunks = vec![String::new(); threads];
 
-            // print ascii
-            print_frame_part("│ ");
-            for i in 0..16 {
-                let addr = base + i;
-                if addr < self.start_addr || addr > self.end_addr {
-                    print!(" ");
-                } else {
-                    let byte = self.data[addr as usize];
-                    if byte.is_ascii_graphic() || byte == b' ' {
-                        print!("{}", byte as char);
-                    } else {
-                        print_dark(".");
+        let batch_rows = threads as u64 * ROWS_PER_CHUNK;
+        for batch_base in rows.clone().step_by(batch_rows.max(1) as usize) {
+            let ranges = (0..threads as u64).map(|i| {
+                let first = batch_base + i * ROWS_PER_CHUNK;
+                first.min(rows.end)..(first + ROWS_PER_CHUNK).min(rows.end)
+            });
+            if threads == 1 {
+                for (lines, chunk) in ranges.zip(chunks.iter_mut()) {
+                    self.format_rows(palette, lines, hex_width, chunk);
+                }
+            } else {
+                thread::scope(|scope| {
+                    for (lines, chunk) in ranges.zip(chunks.iter_mut()) {
+                        scope.spawn(move || self.format_rows(palette, lines, hex_width, chunk));
                     }
+                });
+            }
+            for chunk in &chunks {
+                w.write_all(chunk.as_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    // replaces chunk with the rows of lines
+    fn format_rows(
+        &self,
+        palette: &RowPalette,
+        lines: Range<u64>,
+        hex_width: usize,
+        chunk: &mut String,
+    ) {
+        chunk.clear();
+        for line in lines {
+            self.format_row(palette, line * 16, hex_width, chunk);
+        }
+    }
+
+    // appends a row that shows text instead of bytes (eg: the separator that replaces the
+    // rows of a skip) to row, extra_width spans the columns right of the ASCII column
+    fn format_note_row(
+        palette: &RowPalette,
+        text: &str,
+        hex_width: usize,
+        extra_width: usize,
+        row: &mut String,
+    ) {
+        palette.frame_part(row, "│");
+        row.push_str("  ");
+        palette.dark(row, &"·".repeat(hex_width));
+        row.push_str("  ");
+        palette.wall(row);
+
+        // the text fills the hex and ascii columns, longer texts are cut at the front
+        let width = NOTE_TEXT_WIDTH + extra_width;
+        let text = Self::fit_note(text, width);
+        palette.dark(row, &text);
+        row.push_str(&" ".repeat(width - text.chars().count()));
+        palette.frame_part(row, "│");
+        row.push('\n');
+    }
+
+    // appends the row at base (including its line break) to row
+    fn format_row(&self, palette: &RowPalette, base: u64, hex_width: usize, row: &mut String) {
+        if let Some(order) = self.bit_order {
+            return self.format_bit_row(palette, order, base, hex_width, row);
+        }
+
+        // base addr
+        palette.frame_part(row, "│");
+        // the BASE column is hex_width + 4 wide
+        row.push_str("  ");
+        palette.base_addr(row, base, hex_width);
+        row.push_str("  ");
+        palette.wall(row);
+
+        // data
+        let bytes = (0..16).map(|i| {
+            let addr = base + i;
+            // None: out of range, Some(None): unset byte
+            self.in_range(addr).then(|| self.byte(addr))
+        });
+        let bytes: Vec<Option<Option<u8>>> = bytes.collect();
+        for (i, byte) in bytes.iter().enumerate() {
+            match byte {
+                Some(Some(data)) if !self.marks.is_empty() && self.marked(base + i as u64) => {
+                    palette.marked_hex(row, *data)
                 }
+                Some(data) => palette.hex(row, *data),
+                None => row.push_str("   "), // out of range padding
+            }
+
+            if i == 7 {
+                palette.wall(row);
+            } else if i != 15 {
+                row.push(' ');
             }
-            print_frame_part(" │");
-            println!();
         }
-        print_frame_foot(left_base_padding, right_base_padding);
+
+        // text (ascii unless --encoding is passed)
+        palette.wall(row);
+        self.format_text(palette, base, &bytes, row);
+
+        // checksum of the block that ends in the row (--block-hash)
+        if let Some(width) = self.block_hash_width() {
+            palette.frame_part(row, " ");
+            palette.wall(row);
+            let last = (base..base + 16).rev().find(|&addr| self.in_range(addr));
+            let checksum = last.and_then(|last| self.block_checksum(last));
+            row.push_str(&format!("{:width$}", checksum.unwrap_or_default()));
+        }
+        palette.frame_part(row, " │");
+        row.push('\n');
     }
 }
 
@@ -468,8 +1489,28 @@ mod tests {
         assert_eq!(Dump::calc_hex_width(0xFFFFFFFF), 8);
     }
 
+    #[test]
+    fn empty_dump_test() {
+        let mut out = Vec::new();
+        Dump::from_bytes(Vec::new()).write_to(&mut out).unwrap();
+        let text = strip_escapes(&String::from_utf8(out).unwrap());
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines[0..2],
+            ["Filesize: 0 bytes", "Dumping 0 bytes at 0000"]
+        );
+        // head and foot of the frame without rows
+        assert_eq!(lines.len(), 3 + 3 + 1);
+        assert!(lines[6].starts_with("└"));
+
+        let dump = Dump::from_bytes(vec![0; 32]).range(16..16);
+        assert_eq!(dump.rows(), 0..0);
+        assert_eq!(dump.last_addr(), None);
+    }
+
     #[test]
     fn format_filesize_test() {
+        assert_eq!(Dump::format_filesize(0), "0 bytes");
         assert_eq!(Dump::format_filesize(1023), "1023 bytes (EOF: 03FE)");
         assert_eq!(
             Dump::format_filesize(1024),
@@ -484,4 +1525,145 @@ mod tests {
             "1.0 GB (1073741824 bytes, EOF: 3FFFFFFF)"
         );
     }
+    #[test]
+    fn write_to_test() {
+        let mut out = Vec::new();
+        Dump::from_bytes(b"Hello".to_vec())
+            .range(1..3)
+            .format(OutputFormat::JsonLines)
+            .write_to(&mut out)
+            .unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("{\"type\":\"header\",\"file\":null,"));
+        assert!(
+            text.contains("{\"type\":\"row\",\"address\":1,\"bytes\":[101,108],\"ascii\":\"el\"}")
+        );
+    }
+
+    #[test]
+    fn range_test() {
+        let dump = Dump::from_bytes(b"Hello".to_vec());
+        assert!(dump.check_range().is_ok());
+        let dump = dump.range(2..);
+        assert_eq!((dump.start_addr, dump.num_bytes), (2, 3));
+        assert!(dump.range(3..=5).check_range().is_err());
+        assert!(Dump::from_bytes([1, 2]).range(1..1).check_range().is_ok());
+        assert!(Dump::from_bytes([1, 2]).range(3..3).check_range().is_err());
+        assert!(Dump::from_bytes(Vec::new()).check_range().is_ok());
+        assert!(matches!(
+            Dump::from_bytes([1, 2])
+                .block_hash(HashAlgo::Crc32, 24)
+                .write_to(io::sink()),
+            Err(Error::InvalidBlockSize(24))
+        ));
+    }
+
+    #[test]
+    fn base_addr_test() {
+        let dump = Dump::from_bytes(b"Hello".to_vec()).base_addr(0x0800_0000);
+        assert_eq!((dump.start_addr, dump.file_end), (0x0800_0000, 0x0800_0005));
+        assert_eq!(dump.byte(0x0800_0001), Some(b'e'));
+        assert!(dump.range(0..2).check_range().is_err());
+        assert!(matches!(
+            Dump::from_bytes(b"Hello".to_vec())
+                .base_addr(u64::MAX - 2)
+                .check_range(),
+            Err(Error::BaseAddrOverflow { .. })
+        ));
+    }
+
+    #[test]
+    fn ranges_test() {
+        let dump = Dump::from_bytes(vec![0; 0x100]).ranges([0x80..0x90, 0x0..0x20, 0x10..0x28]);
+        assert_eq!((dump.start_addr, dump.num_bytes), (0x0, 0x90));
+        assert_eq!(dump.skips, vec![0x28..0x80]);
+        assert_eq!(dump.dumped_bytes(), 0x38);
+        assert!(dump.in_range(0x27) && !dump.in_range(0x28) && dump.in_range(0x80));
+        assert_eq!(
+            dump.row_runs(),
+            vec![
+                (None, 0..3),
+                (Some("88 bytes skipped (0028 to 007F)".to_string()), 8..9)
+            ]
+        );
+        assert_eq!(dump.row_segments(0x20), vec![0x20..=0x27]);
+    }
+
+    #[test]
+    fn check_args_test() {
+        use clap::Parser;
+        let check = |args: &[&str]| {
+            let cli = Cli::parse_from([&["hexdump", "file"], args].concat());
+            Dump::from_bytes(vec![0; 32]).check_args(&cli)
+        };
+        assert!(check(&["-s", "8", "-n", "24"]).is_ok());
+        assert!(matches!(
+            check(&["-s", "32"]),
+            Err(Error::StartExceedsMax { start: 32, max: 31 })
+        ));
+        assert!(matches!(
+            check(&["-s", "8", "-n", "25"]),
+            Err(Error::CalcEndExceedsMax { end: 32, max: 31 })
+        ));
+        assert!(check(&["-n", "0"]).is_ok());
+        assert!(check(&["-n", "0", "-e", "31"]).is_ok());
+        assert!(check(&["--base-addr", "0x100", "-s", "0x110", "-e", "-1"]).is_ok());
+        assert!(matches!(
+            check(&["--base-addr", "0x100", "-s", "0x10"]),
+            Err(Error::StartBeforeBase {
+                start: 0x10,
+                base: 0x100
+            })
+        ));
+        assert!(matches!(
+            check(&["-s", "1", "-n", "1", "-e", "1"]),
+            Err(Error::InvalidArgCombination)
+        ));
+        assert!(matches!(
+            check(&["-s", "13bit"]),
+            Err(Error::BitRangeNeedsBits)
+        ));
+        let cli = Cli::parse_from(["hexdump", "file", "-s", "13bit", "-n", "11bit", "--bits"]);
+        let mut dump = Dump::from_bytes(vec![0; 32]);
+        dump.check_args(&cli).unwrap();
+        assert_eq!((dump.start_addr, dump.num_bytes), (1, 2));
+        assert_eq!(dump.bit_range, Some(13..24));
+        // -n -e in bits: the bits before the end, in the bytes they lie in
+        let cli = Cli::parse_from(["hexdump", "file", "-n", "11bit", "-e", "17bit", "--bits"]);
+        let mut dump = Dump::from_bytes(vec![0; 32]);
+        dump.check_args(&cli).unwrap();
+        assert_eq!((dump.start_addr, dump.num_bytes), (0, 3));
+        assert_eq!(dump.bit_range, Some(7..18));
+        assert!(matches!(
+            check(&["-n", "19bit", "-e", "17bit", "--bits"]),
+            Err(Error::NumExceedsEnd { .. })
+        ));
+    }
+
+    #[test]
+    fn format_row_test() {
+        let dump = Dump::from_bytes(b"Hi\0".to_vec());
+        let mut row = String::new();
+        dump.format_row(&RowPalette::new(VisuMode::Default), 0, 4, &mut row);
+        assert_eq!(
+            strip_escapes(&row),
+            "│  0000  │ 48  69  00                     │                                │ Hi.              │\n"
+        );
+    }
+    #[test]
+    fn threads_test() {
+        // the chunks of the threads have to be joined in order
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i * 7) as u8).collect();
+        let render = |threads| {
+            let mut out = Vec::new();
+            Dump::from_bytes(data.clone())
+                .range(5..99_990)
+                .threads(threads)
+                .write_to(&mut out)
+                .unwrap();
+            out
+        };
+        assert_eq!(render(1), render(3));
+        assert_eq!(render(1), render(0));
+    }
 }
@@ -0,0 +1,153 @@
+use super::Dump;
+use crate::color::RowPalette;
+use std::ops::Range;
+
+// Bit view (--bits): every byte is shown as its 8 bits, the 16 bytes of a row are split into
+// two lines of 8 bytes. Bit positions count the bits in the shown order from address 0 on,
+// bit granular -s, -n and -e (13bit) blank the bits of the dumped bytes outside of them.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BitOrder {
+    Msb, // --bits msb: the most significant bit first (bit 7 to 0)
+    Lsb, // --bits lsb: the least significant bit first (bit 0 to 7)
+}
+
+impl BitOrder {
+    // the order of a --bits value
+    pub fn parse(name: &str) -> Option<BitOrder> {
+        match name.to_lowercase().as_str() {
+            "msb" => Some(BitOrder::Msb),
+            "lsb" => Some(BitOrder::Lsb),
+            _ => None,
+        }
+    }
+
+    // index of the bit shown at position pos (0..8) of a byte
+    fn bit(&self, pos: u64) -> u64 {
+        match self {
+            BitOrder::Msb => 7 - pos,
+            BitOrder::Lsb => pos,
+        }
+    }
+
+    // the bit indices above the bits of a byte (in the frame head)
+    pub(super) fn indices(&self) -> &'static str {
+        match self {
+            BitOrder::Msb => "76543210",
+            BitOrder::Lsb => "01234567",
+        }
+    }
+}
+
+impl Dump {
+    /// Shows every byte of the text dump as its 8 bits in order (eg: for protocol fields that
+    /// aren't byte aligned). The rows are split into two lines of 8 bytes.
+    pub fn bits(mut self, order: BitOrder) -> Dump {
+        self.bit_order = Some(order);
+        self
+    }
+
+    /// Limits the bit view to the bits of range, bit n is at position n % 8 of the byte at
+    /// address n / 8. The dump range is set to the bytes the bits lie in.
+    pub fn bit_range(mut self, range: Range<u64>) -> Dump {
+        self = self.range(range.start / 8..range.end.div_ceil(8));
+        self.bit_range = Some(range);
+        self
+    }
+
+    // the line below the Dumping line of the bit view
+    pub(super) fn bit_line(&self) -> Option<String> {
+        let order = match self.bit_order? {
+            BitOrder::Msb => "MSB first",
+            BitOrder::Lsb => "LSB first",
+        };
+        Some(match &self.bit_range {
+            Some(range) if range.is_empty() => format!("Showing 0 bits at bit {}", range.start),
+            Some(range) => format!(
+                "Showing {} bits from bit {} to bit {}, {order}",
+                range.end - range.start,
+                range.start,
+                range.end - 1
+            ),
+            None => format!("Showing the bits {order}"),
+        })
+    }
+
+    // appends the lines of the row at base in the bit view to row, lines without a dumped byte
+    // are left out
+    pub(super) fn format_bit_row(
+        &self,
+        palette: &RowPalette,
+        order: BitOrder,
+        base: u64,
+        hex_width: usize,
+        row: &mut String,
+    ) {
+        for line in [base, base + 8] {
+            // None: out of range, Some(None): unset byte
+            let bytes: Vec<Option<Option<u8>>> = (line..line + 8)
+                .map(|addr| self.in_range(addr).then(|| self.byte(addr)))
+                .collect();
+            if bytes.iter().all(Option::is_none) {
+                continue;
+            }
+            palette.frame_part(row, "│");
+            row.push_str("  ");
+            palette.base_addr(row, line, hex_width);
+            row.push_str("  ");
+            palette.wall(row);
+
+            for (i, byte) in bytes.iter().enumerate() {
+                let addr = line + i as u64;
+                match byte {
+                    Some(Some(data)) => {
+                        for pos in 0..8 {
+                            let shown = self.bit_range.as_ref().is_none_or(|range| {
+                                range.contains(&addr.saturating_mul(8).saturating_add(pos))
+                            });
+                            palette.bit(row, shown.then(|| data >> order.bit(pos) & 1 == 1));
+                        }
+                    }
+                    Some(None) => palette.dark(row, "--------"),
+                    None => row.push_str("        "), // out of range padding
+                }
+                row.push(' ');
+                if i == 3 || i == 7 {
+                    palette.wall(row);
+                }
+            }
+
+            self.format_text(palette, line, &bytes, row);
+            palette.frame_part(row, " │");
+            row.push('\n');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::strip_escapes;
+    use crate::dump::VisuMode;
+
+    #[test]
+    fn format_bit_row_test() {
+        let palette = RowPalette::new(VisuMode::Default);
+        let row = |dump: &Dump, order| {
+            let mut row = String::new();
+            dump.format_bit_row(&palette, order, 0, 4, &mut row);
+            strip_escapes(&row)
+        };
+        let dump = Dump::from_bytes(*b"Hi\x01");
+        assert_eq!(
+            row(&dump, BitOrder::Msb),
+            "│  0000  │ 01001000 01101001 00000001          │                                     │ Hi.      │\n"
+        );
+        assert!(row(&dump, BitOrder::Lsb).contains(" 00010010 10010110 10000000 "));
+
+        // bits 13 to 23
+        let dump = Dump::from_bytes(*b"Hi\x01").bit_range(13..24);
+        assert_eq!((dump.start_addr, dump.num_bytes), (1, 2));
+        assert!(row(&dump, BitOrder::Msb).contains("          ·····001 00000001 "));
+    }
+}
@@ -0,0 +1,212 @@
+// Decoders for binary data that is stored as text, whitespace between the encoded chars is ignored
+
+pub type Decoder = fn(&str) -> Result<Vec<u8>, String>;
+
+fn strip_whitespace(text: &str) -> Vec<u8> {
+    text.bytes().filter(|b| !b.is_ascii_whitespace()).collect()
+}
+
+// decodes groups of chars with bits_per_char bits each (base64, base32)
+fn decode_bits(
+    text: &str,
+    bits_per_char: u32,
+    value: fn(u8) -> Option<u8>,
+) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    let mut padded = false;
+
+    for c in strip_whitespace(text) {
+        // padding is only allowed at the end of the data
+        if c == b'=' {
+            padded = true;
+            continue;
+        }
+        if padded {
+            return Err(format!("data after padding ('{}')", c as char));
+        }
+        let v = value(c).ok_or_else(|| format!("invalid char '{}'", c as char))?;
+        buffer = (buffer << bits_per_char) | v as u32;
+        bits += bits_per_char;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Ok(out)
+}
+
+pub fn base64(text: &str) -> Result<Vec<u8>, String> {
+    // accepts the standard and the url safe alphabet
+    decode_bits(text, 6, |c| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' | b'-' => Some(62),
+        b'/' | b'_' => Some(63),
+        _ => None,
+    })
+}
+
+pub fn base32(text: &str) -> Result<Vec<u8>, String> {
+    decode_bits(text, 5, |c| match c.to_ascii_uppercase() {
+        c @ b'A'..=b'Z' => Some(c - b'A'),
+        c @ b'2'..=b'7' => Some(c - b'2' + 26),
+        _ => None,
+    })
+}
+
+pub fn ascii85(text: &str) -> Result<Vec<u8>, String> {
+    let mut chars = strip_whitespace(text);
+    // optional <~ ~> delimiters
+    if chars.starts_with(b"<~") {
+        chars.drain(..2);
+    }
+    if chars.ends_with(b"~>") {
+        chars.truncate(chars.len() - 2);
+    }
+
+    let mut out = Vec::new();
+    let mut group: Vec<u8> = Vec::with_capacity(5);
+    for c in chars {
+        match c {
+            b'z' if group.is_empty() => out.extend_from_slice(&[0; 4]),
+            b'!'..=b'u' => group.push(c - b'!'),
+            _ => return Err(format!("invalid char '{}'", c as char)),
+        }
+        if group.len() == 5 {
+            let value = group.iter().fold(0u64, |v, &d| v * 85 + d as u64);
+            if value > u32::MAX as u64 {
+                return Err("invalid group".to_string());
+            }
+            out.extend_from_slice(&(value as u32).to_be_bytes());
+            group.clear();
+        }
+    }
+
+    // a partial group is padded with 'u' and only keeps its len - 1 bytes
+    if group.len() == 1 {
+        return Err("invalid final group".to_string());
+    }
+    if !group.is_empty() {
+        let len = group.len();
+        group.resize(5, b'u' - b'!');
+        let value = group.iter().fold(0u64, |v, &d| v * 85 + d as u64);
+        if value > u32::MAX as u64 {
+            return Err("invalid final group".to_string());
+        }
+        out.extend_from_slice(&(value as u32).to_be_bytes()[..len - 1]);
+    }
+    Ok(out)
+}
+
+pub fn hex_string(text: &str) -> Result<Vec<u8>, String> {
+    // every group of digits may have a 0x prefix (eg: 0x41 0x42)
+    let digits: Vec<u8> = text
+        .split_ascii_whitespace()
+        .flat_map(|group| {
+            let digits = group
+                .strip_prefix("0x")
+                .or_else(|| group.strip_prefix("0X"));
+            digits.unwrap_or(group).bytes()
+        })
+        .collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err("odd number of hex digits".to_string());
+    }
+
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let pair = String::from_utf8_lossy(pair);
+            u8::from_str_radix(&pair, 16).map_err(|_| format!("invalid hex digits '{pair}'"))
+        })
+        .collect()
+}
+
+// decodes a string with \x.. escapes (like a C or Rust string literal)
+pub fn escaped(text: &str) -> Result<Vec<u8>, String> {
+    let mut text = text.trim();
+    // optional quotes around the string
+    if text.len() >= 2 && text.starts_with('"') && text.ends_with('"') {
+        text = &text[1..text.len() - 1];
+    }
+
+    let mut out = Vec::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+
+        match chars.next() {
+            Some('x') => {
+                // exactly two hex digits (from_str_radix would take \x4 and \x+4)
+                let digits: String = chars.by_ref().take(2).collect();
+                if digits.len() != 2 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(format!("invalid escape '\\x{digits}'"));
+                }
+                out.push(u8::from_str_radix(&digits, 16).unwrap());
+            }
+            Some('n') => out.push(b'\n'),
+            Some('r') => out.push(b'\r'),
+            Some('t') => out.push(b'\t'),
+            Some('0') => out.push(0),
+            Some(c @ ('\\' | '"' | '\'')) => out.push(c as u8),
+            Some(c) => return Err(format!("invalid escape '\\{c}'")),
+            None => return Err("unfinished escape at the end".to_string()),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_test() {
+        assert_eq!(base64("SGVsbG8="), Ok(b"Hello".to_vec()));
+        assert_eq!(base64("SGVs\nbG8"), Ok(b"Hello".to_vec()));
+        assert_eq!(base64("__--"), Ok(vec![0xFF, 0xFF, 0xBE]));
+        assert!(base64("SGV*").is_err());
+        assert!(base64("QUJD=REVG").is_err());
+        assert_eq!(base64("QQ=\n="), Ok(b"A".to_vec()));
+    }
+
+    #[test]
+    fn base32_test() {
+        assert_eq!(base32("JBSWY3DP"), Ok(b"Hello".to_vec()));
+        assert_eq!(base32("mzxw6==="), Ok(b"foo".to_vec()));
+        assert!(base32("JBSWY3D1").is_err());
+    }
+
+    #[test]
+    fn ascii85_test() {
+        assert_eq!(ascii85("<~87cURDZ~>"), Ok(b"Hello".to_vec()));
+        assert_eq!(ascii85("z"), Ok(vec![0; 4]));
+        assert!(ascii85("87cUR{").is_err());
+    }
+
+    #[test]
+    fn hex_string_test() {
+        assert_eq!(hex_string("48 65 6c\n6C 6F"), Ok(b"Hello".to_vec()));
+        assert_eq!(hex_string("0xDEADbeef"), Ok(vec![0xDE, 0xAD, 0xBE, 0xEF]));
+        assert_eq!(hex_string("0x41 0X42\n0x43"), Ok(b"ABC".to_vec()));
+        assert!(hex_string("123").is_err());
+    }
+
+    #[test]
+    fn escaped_test() {
+        assert_eq!(escaped("\"\\x48i\\n\"\n"), Ok(b"Hi\n".to_vec()));
+        assert_eq!(escaped("\\x00\\xff\\\\"), Ok(vec![0, 0xFF, b'\\']));
+        assert!(escaped("\\xZZ").is_err());
+        assert!(escaped("\\x4").is_err());
+        assert!(escaped("\\x+4").is_err());
+        assert!(escaped("\\q").is_err());
+    }
+}
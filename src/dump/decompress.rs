@@ -0,0 +1,108 @@
+use std::io::Read;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+    Xz,
+    Bzip2,
+}
+
+impl Compression {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+            Compression::Xz => "xz",
+            Compression::Bzip2 => "bzip2",
+        }
+    }
+
+    // detects the compression format by the magic number at the start of data
+    pub fn detect(data: &[u8]) -> Option<Compression> {
+        if data.starts_with(&[0x1F, 0x8B]) {
+            Some(Compression::Gzip)
+        } else if data.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+            Some(Compression::Zstd)
+        } else if data.starts_with(&[0xFD, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Compression::Xz)
+        } else if data.starts_with(b"BZh") {
+            Some(Compression::Bzip2)
+        } else {
+            None
+        }
+    }
+
+    pub fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        match self {
+            Compression::Gzip => {
+                flate2::read::MultiGzDecoder::new(data)
+                    .read_to_end(&mut out)
+                    .map_err(|e| e.to_string())?;
+            }
+            Compression::Zstd => {
+                // a zstd stream can consist of multiple frames
+                let mut input = data;
+                while !input.is_empty() {
+                    ruzstd::decoding::StreamingDecoder::new(&mut input)
+                        .map_err(|e| e.to_string())?
+                        .read_to_end(&mut out)
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+            Compression::Xz => {
+                lzma_rs::xz_decompress(&mut &data[..], &mut out).map_err(|e| e.to_string())?;
+            }
+            Compression::Bzip2 => {
+                bzip2::read::MultiBzDecoder::new(data)
+                    .read_to_end(&mut out)
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn detect_test() {
+        assert_eq!(
+            Compression::detect(&[0x1F, 0x8B, 0x08]),
+            Some(Compression::Gzip)
+        );
+        assert_eq!(
+            Compression::detect(&[0x28, 0xB5, 0x2F, 0xFD, 0x00]),
+            Some(Compression::Zstd)
+        );
+        assert_eq!(Compression::detect(b"BZh91AY"), Some(Compression::Bzip2));
+        assert_eq!(Compression::detect(b"hello"), None);
+    }
+
+    #[test]
+    fn decompress_test() {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"hello hexdump").unwrap();
+        let gzip = encoder.finish().unwrap();
+        assert_eq!(Compression::detect(&gzip), Some(Compression::Gzip));
+        assert_eq!(
+            Compression::Gzip.decompress(&gzip),
+            Ok(b"hello hexdump".to_vec())
+        );
+
+        let mut bzip2 = Vec::new();
+        bzip2::read::BzEncoder::new(&b"hello hexdump"[..], bzip2::Compression::default())
+            .read_to_end(&mut bzip2)
+            .unwrap();
+        assert_eq!(
+            Compression::Bzip2.decompress(&bzip2),
+            Ok(b"hello hexdump".to_vec())
+        );
+
+        assert!(Compression::Gzip.decompress(b"no gzip").is_err());
+    }
+}
@@ -0,0 +1,396 @@
+use super::Dump;
+use crate::color::RowPalette;
+use std::sync::OnceLock;
+use unicode_width::UnicodeWidthChar;
+
+// Text panel encodings (--encoding): the bytes of the panel right of the hex columns are
+// decoded as text. A character of several bytes is drawn in the cell of its first byte and
+// spans the cells of its other bytes, bytes that aren't a printable character are shown as dots.
+
+// characters of the bytes 0x80 to 0xFF in code page 437 (IBM PC)
+const CP437_HIGH: &str = "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜ¢£¥₧ƒáíóúñÑªº¿⌐¬½¼¡«»░▒▓│┤╡╢╖╕╣║╗╝╜╛┐└┴┬├─┼╞╟╚╔╩╦╠═╬╧╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀αßΓπΣσµτΦΘΩδ∞φε∩≡±≥≤⌠⌡÷≈°∙·√ⁿ²■\u{a0}";
+
+// glyphs code page 437 shows for the control bytes 0x01 to 0x1F
+const CP437_CONTROL: &str = "☺☻♥♦♣♠•◘○◙♂♀♪♫☼►◄↕‼¶§▬↨↑↓→←∟↔▲▼";
+
+// characters of the bytes 0x40 to 0xFE in EBCDIC code page 037 (the others are controls)
+const EBCDIC: &str = " \u{a0}âäàáãåçñ¢.<(+|&éêëèíîïìß!$*);¬-/ÂÄÀÁÃÅÇÑ¦,%_>?øÉÊËÈÍÎÏÌ`:#@'=\"Øabcdefghi«»ðýþ±°jklmnopqrªºæ¸Æ¤µ~stuvwxyz¡¿ÐÝÞ®^£¥·©§¶¼½¾[]¯¨´×{ABCDEFGHI\u{ad}ôöòóõ}JKLMNOPQR¹ûüùúÿ\\÷STUVWXYZ²ÔÖÒÓÕ0123456789³ÛÜÙÚ";
+
+// bytes a Shift-JIS lookback passes at most to find the start of a character
+const SJIS_LOOKBACK: u64 = 256;
+
+// the shown character of every byte of a single byte encoding, None for bytes shown as dots
+type SingleTable = [Option<char>; 256];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Ascii,    // no --encoding arg was passed
+    Utf8,     // --encoding utf8
+    Utf16Le,  // --encoding utf16le
+    Utf16Be,  // --encoding utf16be
+    Latin1,   // --encoding latin1   (ISO 8859-1)
+    Cp437,    // --encoding cp437    (IBM PC, glyphs for control bytes)
+    Ebcdic,   // --encoding ebcdic   (code page 037)
+    ShiftJis, // --encoding shiftjis
+}
+
+// the panel cell of a byte
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Cell {
+    Blank,             // out of range, unset or inside of a character that started before
+    Dot,               // not (the start of) a printable character
+    Char(char, usize), // a character that spans this and the next cells (of the same line)
+    Spanned,           // inside of a character that started in this line
+}
+
+impl Encoding {
+    // the encoding of an --encoding value
+    pub fn parse(name: &str) -> Option<Encoding> {
+        match name.to_lowercase().replace(['-', '_'], "").as_str() {
+            "ascii" => Some(Encoding::Ascii),
+            "utf8" => Some(Encoding::Utf8),
+            "utf16le" | "utf16" => Some(Encoding::Utf16Le),
+            "utf16be" => Some(Encoding::Utf16Be),
+            "latin1" | "iso88591" => Some(Encoding::Latin1),
+            "cp437" => Some(Encoding::Cp437),
+            "ebcdic" | "cp037" => Some(Encoding::Ebcdic),
+            "shiftjis" | "sjis" => Some(Encoding::ShiftJis),
+            _ => None,
+        }
+    }
+
+    // header of the text panel
+    pub fn title(&self) -> &'static str {
+        match self {
+            Encoding::Ascii => "ASCII",
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Latin1 => "Latin-1",
+            Encoding::Cp437 => "CP437",
+            Encoding::Ebcdic => "EBCDIC",
+            Encoding::ShiftJis => "Shift-JIS",
+        }
+    }
+
+    // bytes a character consists of at least
+    fn unit_len(&self) -> u64 {
+        match self {
+            Encoding::Utf16Le | Encoding::Utf16Be => 2,
+            _ => 1,
+        }
+    }
+
+    // the character of a byte of a single byte encoding (looked up in the strings, the tables
+    // of single_table cache it)
+    fn single_char(&self, byte: u8) -> Option<char> {
+        match (self, byte) {
+            (Encoding::Latin1, _) => Some(byte as char),
+            (Encoding::Cp437, 0x01..=0x1F) => CP437_CONTROL.chars().nth(byte as usize - 1),
+            (Encoding::Cp437, 0x7F) => Some('⌂'),
+            (Encoding::Cp437, 0x80..) => CP437_HIGH.chars().nth(byte as usize - 0x80),
+            (Encoding::Ebcdic, 0x40..=0xFE) => EBCDIC.chars().nth(byte as usize - 0x40),
+            (Encoding::Ebcdic, _) => None,
+            // the ascii part of the other encodings
+            _ => Some(byte as char),
+        }
+    }
+
+    // the shown characters of the bytes of a single byte encoding (built once), None for the
+    // other encodings
+    fn single_table(&self) -> Option<&'static SingleTable> {
+        static TABLES: [OnceLock<SingleTable>; 3] = [const { OnceLock::new() }; 3];
+        let index = match self {
+            Encoding::Latin1 => 0,
+            Encoding::Cp437 => 1,
+            Encoding::Ebcdic => 2,
+            _ => return None,
+        };
+        Some(TABLES[index].get_or_init(|| {
+            // control characters and characters that don't fit into one cell are shown as dots
+            std::array::from_fn(|byte| {
+                let c = self.single_char(byte as u8);
+                c.filter(|c| !c.is_control() && c.width() == Some(1))
+            })
+        }))
+    }
+
+    // decodes the character at addr, get returns the dumped bytes. Returns the character and
+    // its length in bytes, None if addr isn't the start of a valid character.
+    fn decode(&self, get: &dyn Fn(u64) -> Option<u8>, addr: u64) -> Option<(char, u64)> {
+        let first = get(addr)?;
+        match self {
+            Encoding::Utf8 => {
+                let len = match first {
+                    0x00..=0x7F => 1,
+                    0xC2..=0xDF => 2,
+                    0xE0..=0xEF => 3,
+                    0xF0..=0xF4 => 4,
+                    _ => return None,
+                };
+                let mut bytes = [first, 0, 0, 0];
+                for i in 1..len {
+                    bytes[i as usize] = get(addr + i)?;
+                }
+                let c = std::str::from_utf8(&bytes[..len as usize])
+                    .ok()?
+                    .chars()
+                    .next()?;
+                Some((c, len))
+            }
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                let unit = |addr: u64| {
+                    let pair = [get(addr)?, get(addr + 1)?];
+                    Some(match self {
+                        Encoding::Utf16Le => u16::from_le_bytes(pair),
+                        _ => u16::from_be_bytes(pair),
+                    })
+                };
+                let first = unit(addr)?;
+                if (0xD800..0xDC00).contains(&first) {
+                    let units = [first, unit(addr + 2)?];
+                    let c = char::decode_utf16(units).next()?.ok()?;
+                    return Some((c, 4));
+                }
+                Some((char::decode_utf16([first]).next()?.ok()?, 2))
+            }
+            Encoding::ShiftJis => match first {
+                0x00..=0x7F => Some((first as char, 1)),
+                // half-width katakana
+                0xA1..=0xDF => Some((char::from_u32(0xFF61 + (first - 0xA1) as u32)?, 1)),
+                _ if is_sjis_lead(first) => {
+                    let bytes = [first, get(addr + 1)?];
+                    let text = encoding_rs::SHIFT_JIS
+                        .decode_without_bom_handling_and_without_replacement(&bytes)?;
+                    let mut chars = text.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => Some((c, 2)),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            },
+            _ => match self.single_table() {
+                Some(table) => Some((table[first as usize]?, 1)),
+                None => Some((first as char, 1)),
+            },
+        }
+    }
+
+    // address the characters that reach into the line at start are decoded from
+    fn sync(&self, get: &dyn Fn(u64) -> Option<u8>, start: u64, first: u64) -> u64 {
+        match self {
+            // back over the continuation bytes to the lead byte
+            Encoding::Utf8 => {
+                let mut addr = start;
+                while addr > start.saturating_sub(3)
+                    && get(addr).is_some_and(|b| (0x80..0xC0).contains(&b))
+                {
+                    addr -= 1;
+                }
+                addr
+            }
+            // the units start at the addresses with the parity of the first dumped byte
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                let addr = start.saturating_sub((start ^ first) & 1);
+                // back to the high surrogate of a pair
+                let high = |addr: u64| match self {
+                    Encoding::Utf16Le => get(addr + 1),
+                    _ => get(addr),
+                };
+                match addr.checked_sub(2) {
+                    Some(prev) if high(prev).is_some_and(|b| (0xD8..0xDC).contains(&b)) => prev,
+                    _ => addr,
+                }
+            }
+            // the run of lead bytes before start is made of pairs that start at its first byte
+            Encoding::ShiftJis => {
+                let mut run = 0;
+                while run < SJIS_LOOKBACK.min(start)
+                    && get(start - run - 1).is_some_and(is_sjis_lead)
+                {
+                    run += 1;
+                }
+                start - run % 2
+            }
+            _ => start,
+        }
+    }
+}
+
+fn is_sjis_lead(byte: u8) -> bool {
+    matches!(byte, 0x81..=0x9F | 0xE0..=0xFC)
+}
+
+impl Dump {
+    /// Sets the encoding the text panel right of the hex columns decodes the bytes with (the
+    /// terminal, html, svg, png and markdown output, json keeps the ascii text).
+    pub fn encoding(mut self, encoding: Encoding) -> Dump {
+        self.encoding = encoding;
+        self
+    }
+
+    // sets cells to the panel cells of the bytes of the line at start (a cell per byte)
+    pub(super) fn text_cells(&self, start: u64, cells: &mut [Cell]) {
+        let get = |addr: u64| self.in_range(addr).then(|| self.byte(addr)).flatten();
+        let end = start + cells.len() as u64;
+        cells.fill(Cell::Blank);
+        let mut addr = self.encoding.sync(&get, start, self.start_addr);
+        while addr < end {
+            let decoded = self.encoding.decode(&get, addr);
+            let cell = addr.checked_sub(start).map(|i| i as usize);
+            match (decoded, cell) {
+                (Some((c, bytes)), Some(i)) => {
+                    let span = bytes.min(end - addr) as usize;
+                    let printable = !c.is_control()
+                        && c.width().is_some_and(|width| width >= 1 && width <= span);
+                    cells[i] = if printable {
+                        Cell::Char(c, span)
+                    } else {
+                        Cell::Dot
+                    };
+                    for cell in &mut cells[i + 1..i + span] {
+                        *cell = Cell::Spanned;
+                    }
+                    addr += bytes;
+                }
+                (Some((_, bytes)), None) => addr += bytes,
+                (None, _) => {
+                    let unit = addr..(addr + self.encoding.unit_len()).min(end);
+                    for addr in unit.filter(|&addr| addr >= start && get(addr).is_some()) {
+                        cells[(addr - start) as usize] = Cell::Dot;
+                    }
+                    addr += self.encoding.unit_len();
+                }
+            }
+        }
+    }
+
+    // appends the text panel of the line at start to row, bytes are the bytes of the line
+    // (None: out of range, Some(None): unset byte)
+    pub(super) fn format_text(
+        &self,
+        palette: &RowPalette,
+        start: u64,
+        bytes: &[Option<Option<u8>>],
+        row: &mut String,
+    ) {
+        if self.encoding == Encoding::Ascii {
+            for byte in bytes {
+                match byte {
+                    Some(data) => palette.ascii(row, *data),
+                    None => row.push(' '),
+                }
+            }
+            return;
+        }
+        if let Some(table) = self.encoding.single_table() {
+            for byte in bytes {
+                match byte {
+                    Some(Some(data)) => match table[*data as usize] {
+                        Some(c) => row.push(c),
+                        None => palette.dark(row, "."),
+                    },
+                    _ => row.push(' '),
+                }
+            }
+            return;
+        }
+
+        // the lines have 16 bytes (8 in the bit view)
+        let mut cells = [Cell::Blank; 16];
+        let cells = &mut cells[..bytes.len()];
+        self.text_cells(start, cells);
+        for &mut cell in cells {
+            match cell {
+                Cell::Blank => row.push(' '),
+                Cell::Dot => palette.dark(row, "."),
+                Cell::Char(c, span) => {
+                    row.push(c);
+                    let width = c.width().unwrap_or(1);
+                    row.push_str(&" ".repeat(span - width));
+                }
+                Cell::Spanned => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_test() {
+        assert_eq!(CP437_HIGH.chars().count(), 128);
+        assert_eq!(EBCDIC.chars().count(), 0xFF - 0x40);
+        assert_eq!(Encoding::Cp437.single_char(0x01), Some('☺'));
+        assert_eq!(Encoding::Cp437.single_char(0xDB), Some('█'));
+        assert_eq!(Encoding::Ebcdic.single_char(0xC1), Some('A'));
+        assert_eq!(Encoding::Ebcdic.single_char(0xF0), Some('0'));
+        assert_eq!(Encoding::Ebcdic.single_char(0xFF), None);
+        assert_eq!(Encoding::Latin1.single_char(0xE9), Some('é'));
+
+        // the tables show control characters as dots
+        let table = Encoding::Cp437.single_table().unwrap();
+        assert_eq!(
+            (table[0x01], table[0x0A], table[0xDB]),
+            (Some('☺'), Some('◙'), Some('█'))
+        );
+        let table = Encoding::Latin1.single_table().unwrap();
+        assert_eq!(
+            (table[0x0A], table[0x85], table[0xE9]),
+            (None, None, Some('é'))
+        );
+        assert!(Encoding::Utf8.single_table().is_none());
+    }
+
+    #[test]
+    fn text_cells_test() {
+        let cells = |encoding, data: &[u8], start| {
+            let mut cells = [Cell::Blank; 4];
+            Dump::from_bytes(data.to_vec())
+                .encoding(encoding)
+                .text_cells(start, &mut cells);
+            cells
+        };
+        // "aé€" in UTF-8, the € is cut by the end of the line
+        assert_eq!(
+            cells(Encoding::Utf8, b"a\xC3\xA9\xE2\x82\xAC", 0),
+            [
+                Cell::Char('a', 1),
+                Cell::Char('é', 2),
+                Cell::Spanned,
+                Cell::Char('€', 1)
+            ]
+        );
+        // the line starts inside of a €
+        assert_eq!(
+            cells(Encoding::Utf8, b"\xE2\x82\xAC\xE2\x82\xAC!", 1),
+            [Cell::Blank, Cell::Blank, Cell::Char('€', 2), Cell::Spanned]
+        );
+        assert_eq!(
+            cells(Encoding::Utf8, b"\xFFa\x80\n", 0),
+            [Cell::Dot, Cell::Char('a', 1), Cell::Dot, Cell::Dot]
+        );
+        assert_eq!(
+            cells(Encoding::Utf16Be, b"\0H\x00\xE9", 0),
+            [
+                Cell::Char('H', 2),
+                Cell::Spanned,
+                Cell::Char('é', 2),
+                Cell::Spanned
+            ]
+        );
+        // 日本 in Shift-JIS, the line starts at the trail byte of 日
+        assert_eq!(
+            cells(Encoding::ShiftJis, b"\x93\xFA\x96\x7Bx", 1),
+            [
+                Cell::Blank,
+                Cell::Char('本', 2),
+                Cell::Spanned,
+                Cell::Char('x', 1)
+            ]
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use super::Dump;
+use crate::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::ops::Range;
+
+// Extract mode (carving): the dumped bytes are written raw instead of being dumped, into one
+// file, into files of a fixed size (--split) or to stdout
+
+// path of the part of a split extraction, the number has at least 3 digits
+fn part_path(path: &str, part: u64, parts: u64) -> String {
+    let width = (parts.saturating_sub(1).max(1).ilog10() as usize + 1).max(3);
+    format!("{path}.{part:0width$}")
+}
+
+impl Dump {
+    /// Writes the dumped bytes raw to w, the ranges of several -r args one after another.
+    /// Bytes in gaps of the address space are written as zeros. Returns the number of bytes.
+    pub fn extract_to(&self, mut w: impl Write) -> Result<u64, Error> {
+        self.check_range()?;
+        self.write_raw(&mut w, self.dumped_ranges())?;
+        Ok(self.dumped_bytes())
+    }
+
+    /// Writes the dumped bytes raw to the file at path (- is stdout), split into files of
+    /// split bytes named path.000, path.001, ... if split is set. Returns the written paths.
+    pub fn extract(&self, path: &str, split: Option<u64>) -> Result<Vec<String>, Error> {
+        self.check_range()?;
+        let create = |path: &str| {
+            let file = File::create(path).map_err(|source| Error::Create {
+                path: path.to_string(),
+                source,
+            })?;
+            Ok::<_, Error>(BufWriter::with_capacity(1 << 16, file))
+        };
+
+        match (path, split) {
+            ("-", Some(_)) => Err(Error::SplitNeedsFile),
+            ("-", None) => {
+                let stdout = io::stdout();
+                if stdout.is_terminal() {
+                    return Err(Error::RawToTerminal);
+                }
+                let mut out = BufWriter::with_capacity(1 << 16, stdout.lock());
+                match self.write_raw(&mut out, self.dumped_ranges()) {
+                    // the reader of the output went away (eg: | head -c 16)
+                    Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+                    result => result?,
+                }
+                Ok(Vec::new())
+            }
+            (path, None) => {
+                self.write_raw(&mut create(path)?, self.dumped_ranges())?;
+                Ok(vec![path.to_string()])
+            }
+            (path, Some(split)) => {
+                let parts = self.dumped_bytes().div_ceil(split);
+                let mut paths = Vec::new();
+                for part in 0..parts {
+                    let part_path = part_path(path, part, parts);
+                    let ranges = self.dumped_subranges(part * split, split);
+                    self.write_raw(&mut create(&part_path)?, ranges)?;
+                    paths.push(part_path);
+                }
+                Ok(paths)
+            }
+        }
+    }
+
+    // writes the bytes of ranges to w, bytes in gaps as zeros
+    fn write_raw(&self, w: &mut dyn Write, ranges: Vec<Range<u64>>) -> io::Result<()> {
+        for range in ranges {
+            self.for_each_slice(range, Some(0), &mut |slice| w.write_all(slice))?;
+        }
+        w.flush()
+    }
+
+    // the address ranges of len dumped bytes from the index-th dumped byte on
+    fn dumped_subranges(&self, mut index: u64, mut len: u64) -> Vec<Range<u64>> {
+        let mut ranges = Vec::new();
+        for range in self.dumped_ranges() {
+            let range_len = range.end - range.start;
+            if index >= range_len {
+                index -= range_len;
+                continue;
+            }
+            let start = range.start + index;
+            let end = start + len.min(range.end - start);
+            ranges.push(start..end);
+            (index, len) = (0, len - (end - start));
+            if len == 0 {
+                break;
+            }
+        }
+        ranges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_path_test() {
+        assert_eq!(part_path("blob.bin", 0, 1), "blob.bin.000");
+        assert_eq!(part_path("blob.bin", 7, 1000), "blob.bin.007");
+        assert_eq!(part_path("blob.bin", 7, 1001), "blob.bin.0007");
+    }
+
+    #[test]
+    fn extract_to_test() {
+        let data: Vec<u8> = (0..64).collect();
+        let mut out = Vec::new();
+        let dump = Dump::from_bytes(data).ranges([2..4, 60..62]);
+        assert_eq!(dump.extract_to(&mut out).unwrap(), 4);
+        assert_eq!(out, [2, 3, 60, 61]);
+        assert_eq!(dump.dumped_subranges(1, 2), vec![3..4, 60..61]);
+        assert_eq!(dump.dumped_subranges(3, 2), vec![61..62]);
+    }
+}
@@ -0,0 +1,184 @@
+use super::Dump;
+use crate::Error;
+use crate::color::*;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, IsTerminal, Read, Seek, SeekFrom, Write};
+use std::thread;
+use std::time::Duration;
+
+// Follow mode (tail -f for binaries): the last rows of the file are dumped into an open frame,
+// then the rows of the bytes appended to the file are added as it grows
+
+// time between two checks of the file
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+// the addresses of a growing file get longer, the frame is sized for files up to 4 GB
+const FOLLOW_HEX_WIDTH: usize = 8;
+
+// identifies the file behind a path, changes when the file is replaced (eg: log rotation)
+#[cfg(unix)]
+fn file_id(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(metadata: &fs::Metadata) -> Option<std::time::SystemTime> {
+    metadata.created().ok()
+}
+
+// offset of the first of the last rows rows of a file of len bytes, len for 0 rows
+fn last_rows_start(len: u64, rows: u64) -> u64 {
+    (len.div_ceil(16).saturating_sub(rows) * 16).min(len)
+}
+
+// reads the bytes start..end of the file at path
+fn read_range(path: &str, start: u64, end: u64) -> Result<Vec<u8>, Error> {
+    let read = || -> io::Result<Vec<u8>> {
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(start))?;
+        let mut data = Vec::new();
+        file.take(end - start).read_to_end(&mut data)?;
+        Ok(data)
+    };
+    read().map_err(|source| Error::Read {
+        path: path.to_string(),
+        source,
+    })
+}
+
+impl Dump {
+    /// Creates a dump of the file at path for [`Dump::follow`], only the size of the file is
+    /// read (follow reads the last rows and the bytes appended to it).
+    pub fn for_follow(path: &str) -> Result<Dump, Error> {
+        let metadata = fs::metadata(path).map_err(|source| Error::Read {
+            path: path.to_string(),
+            source,
+        })?;
+
+        // enable ansi support on windows (for colorful console output)
+        #[cfg(windows)]
+        enable_ansi_support();
+
+        let mut dump = Dump::from_layout(Vec::new(), 0, Vec::new());
+        dump.path = Some(path.to_string());
+        dump.filesize = metadata.len();
+        dump.file_end = metadata.len();
+        dump.num_bytes = metadata.len();
+        Ok(dump)
+    }
+
+    /// Dumps the last rows of the file and then keeps adding the rows of the bytes that are
+    /// appended to it, until the process is stopped. Truncating or replacing the file is
+    /// marked by a row and the last rows of the new content are dumped.
+    pub fn follow(&self, rows: u64) -> Result<(), Error> {
+        let path = match self.path.as_deref() {
+            Some(path) if path != "-" => path,
+            _ => return Err(Error::FollowNeedsFile),
+        };
+
+        let stdout = io::stdout();
+        let terminal = stdout.is_terminal();
+        let mut out = BufWriter::with_capacity(1 << 16, stdout.lock());
+        match self.write_follow(&mut out, path, rows, terminal) {
+            // the reader of the output went away (eg: | head)
+            Err(Error::Write(e)) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            result => result,
+        }
+    }
+
+    fn write_follow(
+        &self,
+        w: &mut dyn Write,
+        path: &str,
+        rows: u64,
+        terminal: bool,
+    ) -> Result<(), Error> {
+        let hex_width = self.hex_width().max(FOLLOW_HEX_WIDTH);
+        writeln!(w, "{}", self.filesize_line())?;
+        writeln!(w, "Following {path} (stop with Ctrl+C)")?;
+        writeln!(w)?;
+        let left_base_padding = hex_width / 2;
+        let right_base_padding = hex_width - left_base_padding;
+        let title = self.encoding.title();
+        write_frame_head(w, left_base_padding, right_base_padding, title, None)?;
+
+        // the last rows of the file
+        let palette = RowPalette::new(self.visu_mode);
+        let mut end = self.filesize; // offset after the last dumped byte
+        let mut shown = last_rows_start(end, rows); // offset of the first dumped byte
+        let data = read_range(path, shown, end)?;
+        self.write_follow_rows(w, &palette, &data, shown, hex_width)?;
+        let mut id = fs::metadata(path).ok().and_then(|m| file_id(&m));
+
+        loop {
+            w.flush()?;
+            thread::sleep(POLL_INTERVAL);
+            // a rotated file may not exist until it is created again
+            let Ok(metadata) = fs::metadata(path) else {
+                continue;
+            };
+            let len = metadata.len();
+
+            let new_id = file_id(&metadata);
+            if new_id != id || len < end {
+                let note = if new_id != id {
+                    format!("the file was replaced ({len} bytes)")
+                } else {
+                    format!("the file was truncated to {len} bytes")
+                };
+                let mut row = String::new();
+                Self::format_note_row(&palette, &note, hex_width, 0, &mut row);
+                w.write_all(row.as_bytes())?;
+
+                shown = last_rows_start(len, rows);
+                let data = read_range(path, shown, len)?;
+                self.write_follow_rows(w, &palette, &data, shown, hex_width)?;
+                (id, end) = (new_id, len);
+                continue;
+            }
+            if len == end {
+                continue;
+            }
+
+            // the incomplete last row is dumped again with the new bytes, on a terminal the
+            // old one is overwritten (unless it wasn't dumped, eg: with -F 0)
+            let start = end / 16 * 16;
+            if start != end && start >= shown && terminal {
+                write!(w, "\x1b[1A\x1b[2K")?;
+            }
+            let data = read_range(path, start, len)?;
+            self.write_follow_rows(w, &palette, &data, start, hex_width)?;
+            end = start + data.len() as u64;
+        }
+    }
+
+    // writes the rows of data, which starts at the row aligned file offset
+    fn write_follow_rows(
+        &self,
+        w: &mut dyn Write,
+        palette: &RowPalette,
+        data: &[u8],
+        offset: u64,
+        hex_width: usize,
+    ) -> io::Result<()> {
+        let mut dump = Dump::from_layout(data.to_vec(), self.base_addr + offset, Vec::new());
+        dump.visu_mode = self.visu_mode;
+        dump.encoding = self.encoding;
+        dump.write_rows(w, palette, dump.rows(), hex_width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_rows_start_test() {
+        assert_eq!(last_rows_start(0, 10), 0);
+        assert_eq!(last_rows_start(100, 10), 0);
+        assert_eq!(last_rows_start(0x100, 2), 0xE0);
+        assert_eq!(last_rows_start(0x101, 2), 0xF0);
+        assert_eq!(last_rows_start(300, 0), 300);
+    }
+}
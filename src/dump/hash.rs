@@ -0,0 +1,233 @@
+use super::Dump;
+use crate::Error;
+use crc::Crc;
+use sha2::Digest;
+use std::ops::Range;
+
+// Checksums and hashes of the dump range (--hash) and of the blocks of the dump (--block-hash),
+// bytes in gaps of the address space aren't hashed, holes of sparse files are hashed as zeros
+
+static CRC_8: Crc<u8> = Crc::<u8>::new(&crc::CRC_8_SMBUS);
+static CRC_16: Crc<u16> = Crc::<u16>::new(&crc::CRC_16_ARC);
+static CRC_32: Crc<u32> = Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HashAlgo {
+    Crc8,    // --hash crc8    (CRC-8/SMBUS)
+    Crc16,   // --hash crc16   (CRC-16/ARC)
+    Crc32,   // --hash crc32   (the CRC-32 of zip, gzip and png)
+    Adler32, // --hash adler32
+    Md5,     // --hash md5
+    Sha1,    // --hash sha1
+    Sha256,  // --hash sha256
+    XxHash,  // --hash xxhash  (XXH64 with seed 0)
+}
+
+impl HashAlgo {
+    // the algorithm of a --hash or --block-hash value
+    pub fn parse(name: &str) -> Option<HashAlgo> {
+        match name.to_lowercase().as_str() {
+            "crc8" => Some(HashAlgo::Crc8),
+            "crc16" => Some(HashAlgo::Crc16),
+            "crc32" => Some(HashAlgo::Crc32),
+            "adler32" => Some(HashAlgo::Adler32),
+            "md5" => Some(HashAlgo::Md5),
+            "sha1" => Some(HashAlgo::Sha1),
+            "sha256" => Some(HashAlgo::Sha256),
+            "xxhash" | "xxh64" => Some(HashAlgo::XxHash),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashAlgo::Crc8 => "crc8",
+            HashAlgo::Crc16 => "crc16",
+            HashAlgo::Crc32 => "crc32",
+            HashAlgo::Adler32 => "adler32",
+            HashAlgo::Md5 => "md5",
+            HashAlgo::Sha1 => "sha1",
+            HashAlgo::Sha256 => "sha256",
+            HashAlgo::XxHash => "xxhash",
+        }
+    }
+
+    // name shown in the dump header and above the checksum column
+    pub fn title(&self) -> &'static str {
+        match self {
+            HashAlgo::Crc8 => "CRC-8",
+            HashAlgo::Crc16 => "CRC-16",
+            HashAlgo::Crc32 => "CRC-32",
+            HashAlgo::Adler32 => "Adler-32",
+            HashAlgo::Md5 => "MD5",
+            HashAlgo::Sha1 => "SHA-1",
+            HashAlgo::Sha256 => "SHA-256",
+            HashAlgo::XxHash => "XXH64",
+        }
+    }
+
+    // number of hex digits of a hash
+    pub fn hex_len(&self) -> usize {
+        match self {
+            HashAlgo::Crc8 => 2,
+            HashAlgo::Crc16 => 4,
+            HashAlgo::Crc32 | HashAlgo::Adler32 => 8,
+            HashAlgo::XxHash => 16,
+            HashAlgo::Md5 => 32,
+            HashAlgo::Sha1 => 40,
+            HashAlgo::Sha256 => 64,
+        }
+    }
+
+    fn hasher(&self) -> Hasher {
+        match self {
+            HashAlgo::Crc8 => Hasher::Crc8(CRC_8.digest()),
+            HashAlgo::Crc16 => Hasher::Crc16(CRC_16.digest()),
+            HashAlgo::Crc32 => Hasher::Crc32(CRC_32.digest()),
+            HashAlgo::Adler32 => Hasher::Adler32(adler2::Adler32::new()),
+            HashAlgo::Md5 => Hasher::Md5(md5::Md5::new()),
+            HashAlgo::Sha1 => Hasher::Sha1(sha1::Sha1::new()),
+            HashAlgo::Sha256 => Hasher::Sha256(sha2::Sha256::new()),
+            HashAlgo::XxHash => Hasher::XxHash(xxhash_rust::xxh64::Xxh64::new(0)),
+        }
+    }
+}
+
+enum Hasher {
+    Crc8(crc::Digest<'static, u8>),
+    Crc16(crc::Digest<'static, u16>),
+    Crc32(crc::Digest<'static, u32>),
+    Adler32(adler2::Adler32),
+    Md5(md5::Md5),
+    Sha1(sha1::Sha1),
+    Sha256(sha2::Sha256),
+    XxHash(xxhash_rust::xxh64::Xxh64),
+}
+
+impl Hasher {
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Crc8(digest) => digest.update(data),
+            Hasher::Crc16(digest) => digest.update(data),
+            Hasher::Crc32(digest) => digest.update(data),
+            Hasher::Adler32(adler) => adler.write_slice(data),
+            Hasher::Md5(md5) => md5.update(data),
+            Hasher::Sha1(sha1) => sha1.update(data),
+            Hasher::Sha256(sha256) => sha256.update(data),
+            Hasher::XxHash(xxh) => xxh.update(data),
+        }
+    }
+
+    // the hash as lowercase hex digits (as printed by crc32, md5sum, sha256sum, xxhsum)
+    fn finish(self) -> String {
+        match self {
+            Hasher::Crc8(digest) => format!("{:02x}", digest.finalize()),
+            Hasher::Crc16(digest) => format!("{:04x}", digest.finalize()),
+            Hasher::Crc32(digest) => format!("{:08x}", digest.finalize()),
+            Hasher::Adler32(adler) => format!("{:08x}", adler.checksum()),
+            Hasher::Md5(md5) => hex(&md5.finalize()),
+            Hasher::Sha1(sha1) => hex(&sha1.finalize()),
+            Hasher::Sha256(sha256) => hex(&sha256.finalize()),
+            Hasher::XxHash(xxh) => format!("{:016x}", xxh.digest()),
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+impl Dump {
+    // hash of the dumped bytes in range
+    pub(super) fn hash(&self, algo: HashAlgo, range: Range<u64>) -> String {
+        let mut hasher = algo.hasher();
+        for dumped in self.dumped_ranges() {
+            let (start, end) = (dumped.start.max(range.start), dumped.end.min(range.end));
+            // bytes in gaps are left out
+            self.visit_slices(start..end, None, &mut |slice| hasher.update(slice));
+        }
+        hasher.finish()
+    }
+
+    // the --hash lines of the dump header
+    pub(super) fn hash_lines(&self) -> Vec<String> {
+        let range = self.start_addr..self.start_addr + self.num_bytes;
+        self.hashes
+            .iter()
+            .map(|algo| format!("{}: {}", algo.title(), self.hash(*algo, range.clone())))
+            .collect()
+    }
+
+    // the blocks of --block-hash end at row ends, so their size is a multiple of 16
+    pub(super) fn check_block_size(&self) -> Result<(), Error> {
+        match self.block_hash {
+            Some((_, size)) if size == 0 || !size.is_multiple_of(16) => {
+                Err(Error::InvalidBlockSize(size))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // width of the checksum column (--block-hash), None without it
+    pub(super) fn block_hash_width(&self) -> Option<usize> {
+        let (algo, _) = self.block_hash?;
+        Some(algo.hex_len().max(algo.title().len()))
+    }
+
+    // checksum of the block that ends at last (the last dumped byte of a row), None if the
+    // block goes on after last. Blocks are aligned to the addresses, a block that is cut by the
+    // dump range or a skip only covers its dumped bytes.
+    pub(super) fn block_checksum(&self, last: u64) -> Option<String> {
+        let (algo, block_size) = self.block_hash?;
+        let end = last + 1;
+        if !end.is_multiple_of(block_size) && self.in_range(end) {
+            return None;
+        }
+        let dumped = self
+            .dumped_ranges()
+            .into_iter()
+            .find(|range| range.contains(&last))?;
+        let block_start = last - last % block_size;
+        Some(self.hash(algo, block_start.max(dumped.start)..end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_test() {
+        let dump = Dump::from_bytes(b"123456789".to_vec());
+        let hash = |algo| dump.hash(algo, 0..9);
+        // check values of the algorithms
+        assert_eq!(hash(HashAlgo::Crc8), "f4");
+        assert_eq!(hash(HashAlgo::Crc16), "bb3d");
+        assert_eq!(hash(HashAlgo::Crc32), "cbf43926");
+        assert_eq!(hash(HashAlgo::Adler32), "091e01de");
+        assert_eq!(hash(HashAlgo::Md5), "25f9e794323b453885f5181f1b624d0b");
+        assert_eq!(
+            hash(HashAlgo::Sha1),
+            "f7c3bc1d808e04732adf679965ccc34ca7ae3441"
+        );
+        assert_eq!(
+            hash(HashAlgo::Sha256),
+            "15e2b0d3c33891ebb0f1ef609ec419420c20e320ce94c65fbc8c3312448eb225"
+        );
+        assert_eq!(hash(HashAlgo::XxHash), "8cb841db40e6ae83");
+        assert_eq!(HashAlgo::parse("SHA256"), Some(HashAlgo::Sha256));
+        assert_eq!(HashAlgo::parse("sha512"), None);
+    }
+
+    #[test]
+    fn block_checksum_test() {
+        let data: Vec<u8> = (0..=255).collect();
+        let mut dump = Dump::from_bytes(data).range(0x10..0x70);
+        dump.block_hash = Some((HashAlgo::Crc32, 0x40));
+        let crc = |range: Range<u64>| dump.hash(HashAlgo::Crc32, range);
+        assert_eq!(dump.block_checksum(0x2F), None);
+        assert_eq!(dump.block_checksum(0x3F), Some(crc(0x10..0x40)));
+        // the last block is cut by the dump range
+        assert_eq!(dump.block_checksum(0x6F), Some(crc(0x40..0x70)));
+    }
+}
@@ -0,0 +1,127 @@
+use super::Dump;
+use super::styled::Style;
+use std::io::{self, Write};
+
+// escapes the characters that have a special meaning in html text and attributes
+pub(super) fn html_escape(str: &str) -> String {
+    let mut out = String::with_capacity(str.len());
+    for c in str.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// tooltip of a byte cell, e.g. "offset 0x0010 (16): 0x48 = 72 'H'"
+fn byte_tooltip(offset: u64, data: u8) -> String {
+    let char = if data.is_ascii_graphic() || data == b' ' {
+        format!(" '{}'", data as char)
+    } else {
+        String::new()
+    };
+    format!("offset 0x{offset:04X} ({offset}): 0x{data:02X} = {data}{char}")
+}
+
+const HTML_STYLE: &str = "
+body { background: #1e1e1e; color: #cccccc; }
+pre.dump { font-family: Consolas, 'DejaVu Sans Mono', monospace; line-height: 1.2; }
+[data-o] { cursor: default; }
+[data-o].hover { background: #264f78; }
+";
+
+// highlights the hex and the ascii cell of the same byte together
+const HTML_SCRIPT: &str = "
+const dump = document.querySelector('pre.dump');
+function mark(event, on) {
+  const offset = event.target.dataset ? event.target.dataset.o : undefined;
+  if (offset === undefined) return;
+  dump.querySelectorAll('[data-o=\"' + offset + '\"]').forEach(c => c.classList.toggle('hover', on));
+}
+dump.addEventListener('mouseover', e => mark(e, true));
+dump.addEventListener('mouseout', e => mark(e, false));
+";
+
+impl Dump {
+    pub fn write_html(&self, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "<!DOCTYPE html>")?;
+        writeln!(w, "<html>")?;
+        writeln!(w, "<head>")?;
+        writeln!(w, "<meta charset=\"utf-8\">")?;
+        match &self.path {
+            Some(path) => writeln!(w, "<title>hexdump: {}</title>", html_escape(path))?,
+            None => writeln!(w, "<title>hexdump</title>")?,
+        }
+        writeln!(w, "<style>{HTML_STYLE}")?;
+        let styles = [
+            Style::Frame,
+            Style::Index,
+            Style::Addr,
+            Style::Dark,
+            Style::Highlight(colored::Color::BrightGreen),
+            Style::Highlight(colored::Color::BrightRed),
+            Style::Highlight(colored::Color::BrightMagenta),
+            Style::Highlight(colored::Color::BrightBlue),
+            Style::Highlight(colored::Color::Yellow),
+        ];
+        for style in styles {
+            writeln!(w, ".{} {{ color: {}; }}", style.class(), style.rgb())?;
+        }
+        writeln!(w, "</style>")?;
+        writeln!(w, "</head>")?;
+        writeln!(w, "<body>")?;
+
+        write!(w, "<pre class=\"dump\">")?;
+        for line in self.styled_lines() {
+            for span in line {
+                let text = html_escape(&span.text);
+                match span
+                    .offset
+                    .and_then(|offset| Some((offset, self.byte(offset)?)))
+                {
+                    Some((offset, data)) => {
+                        write!(
+                            w,
+                            "<span class=\"{}\" data-o=\"{offset}\" title=\"{}\">{text}</span>",
+                            span.style.class(),
+                            html_escape(&byte_tooltip(offset, data))
+                        )?;
+                    }
+                    None if span.style == Style::Plain => write!(w, "{text}")?,
+                    None => write!(w, "<span class=\"{}\">{text}</span>", span.style.class())?,
+                }
+            }
+            writeln!(w)?;
+        }
+        writeln!(w, "</pre>")?;
+
+        writeln!(w, "<script>{HTML_SCRIPT}</script>")?;
+        writeln!(w, "</body>")?;
+        writeln!(w, "</html>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn html_escape_test() {
+        assert_eq!(
+            html_escape("<a href=\"x\">&'"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&#39;"
+        );
+        assert_eq!(html_escape("│ 48 65"), "│ 48 65");
+    }
+
+    #[test]
+    fn byte_tooltip_test() {
+        assert_eq!(byte_tooltip(16, 0x48), "offset 0x0010 (16): 0x48 = 72 'H'");
+        assert_eq!(byte_tooltip(0x12345, 0), "offset 0x12345 (74565): 0x00 = 0");
+    }
+}
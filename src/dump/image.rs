@@ -0,0 +1,257 @@
+use super::Dump;
+use super::html::html_escape;
+use super::styled::{Line, Style};
+use crate::Error;
+use embedded_graphics::mono_font::{MonoTextStyle, ascii::FONT_10X20};
+use embedded_graphics::pixelcolor::Rgb888;
+use embedded_graphics::prelude::*;
+use embedded_graphics::text::{Baseline, Text};
+use std::convert::Infallible;
+use std::io::{self, Write};
+
+// Image output formats, both render the lines of styled_lines on the terminal background
+
+const BACKGROUND: &str = "#1e1e1e";
+const IMAGE_PADDING: usize = 16;
+
+// svg cell size (a monospace char is 0.6em wide)
+const SVG_FONT_SIZE: usize = 14;
+const SVG_CHAR_WIDTH: f64 = 8.4;
+const SVG_LINE_HEIGHT: usize = 18;
+
+// png cell size (size of the embedded bitmap font)
+const PNG_CHAR_WIDTH: usize = 10;
+const PNG_LINE_HEIGHT: usize = 20;
+
+// the png is rendered in memory (3 bytes per pixel), larger images are refused (about 3000 rows)
+const MAX_PNG_PIXELS: usize = 1 << 26;
+
+fn line_len(line: &Line) -> usize {
+    line.iter().map(|span| span.text.chars().count()).sum()
+}
+
+// parses a "#rrggbb" color
+fn parse_rgb(rgb: &str) -> Rgb888 {
+    let value = u32::from_str_radix(&rgb[1..], 16).unwrap_or(0);
+    Rgb888::new((value >> 16) as u8, (value >> 8) as u8, value as u8)
+}
+
+// which arms (left, right, up, down) of a box drawing char are drawn
+fn box_arms(c: char) -> Option<(bool, bool, bool, bool)> {
+    match c {
+        '─' => Some((true, true, false, false)),
+        '│' => Some((false, false, true, true)),
+        '┌' => Some((false, true, false, true)),
+        '┐' => Some((true, false, false, true)),
+        '└' => Some((false, true, true, false)),
+        '┘' => Some((true, false, true, false)),
+        '├' => Some((false, true, true, true)),
+        '┤' => Some((true, false, true, true)),
+        '┬' => Some((true, true, false, true)),
+        '┴' => Some((true, true, true, false)),
+        '┼' => Some((true, true, true, true)),
+        _ => None,
+    }
+}
+
+// rgb pixel buffer the png gets rendered into
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize, background: Rgb888) -> Result<Canvas, Error> {
+        if width.saturating_mul(height) > MAX_PNG_PIXELS {
+            return Err(Error::PngTooLarge { width, height });
+        }
+        let pixels = [background.r(), background.g(), background.b()].repeat(width * height);
+        Ok(Canvas {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    fn set_pixel(&mut self, x: usize, y: usize, color: Rgb888) {
+        if x < self.width && y < self.height {
+            let i = (y * self.width + x) * 3;
+            self.pixels[i..i + 3].copy_from_slice(&[color.r(), color.g(), color.b()]);
+        }
+    }
+
+    fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize, color: Rgb888) {
+        for py in y..y + h {
+            for px in x..x + w {
+                self.set_pixel(px, py, color);
+            }
+        }
+    }
+
+    fn draw_char(&mut self, c: char, x: usize, y: usize, color: Rgb888) {
+        if let Some((left, right, up, down)) = box_arms(c) {
+            // box drawing chars are drawn as lines, so the frame connects seamlessly
+            let cx = x + PNG_CHAR_WIDTH / 2;
+            let cy = y + PNG_LINE_HEIGHT / 2;
+            if left {
+                self.fill_rect(x, cy, cx - x + 1, 1, color);
+            }
+            if right {
+                self.fill_rect(cx, cy, x + PNG_CHAR_WIDTH - cx, 1, color);
+            }
+            if up {
+                self.fill_rect(cx, y, 1, cy - y + 1, color);
+            }
+            if down {
+                self.fill_rect(cx, cy, 1, y + PNG_LINE_HEIGHT - cy, color);
+            }
+        } else if c != ' ' {
+            let style = MonoTextStyle::new(&FONT_10X20, color);
+            let mut buf = [0; 4];
+            let point = Point::new(x as i32, y as i32);
+            let _ = Text::with_baseline(c.encode_utf8(&mut buf), point, style, Baseline::Top)
+                .draw(self);
+        }
+    }
+
+    fn encode_png(&self) -> Result<Vec<u8>, png::EncodingError> {
+        let mut png_data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png_data, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()?;
+        Ok(png_data)
+    }
+}
+
+impl OriginDimensions for Canvas {
+    fn size(&self) -> Size {
+        Size::new(self.width as u32, self.height as u32)
+    }
+}
+
+impl DrawTarget for Canvas {
+    type Color = Rgb888;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if point.x >= 0 && point.y >= 0 {
+                self.set_pixel(point.x as usize, point.y as usize, color);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Dump {
+    pub fn write_svg(&self, w: &mut dyn Write) -> io::Result<()> {
+        let lines = self.styled_lines();
+        let columns = lines.iter().map(line_len).max().unwrap_or(0);
+        let width = (columns as f64 * SVG_CHAR_WIDTH).ceil() as usize + 2 * IMAGE_PADDING;
+        let height = lines.len() * SVG_LINE_HEIGHT + 2 * IMAGE_PADDING;
+
+        writeln!(
+            w,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">"
+        )?;
+        writeln!(
+            w,
+            "<rect width=\"100%\" height=\"100%\" rx=\"10\" fill=\"{BACKGROUND}\"/>"
+        )?;
+        writeln!(
+            w,
+            "<text font-family=\"Consolas, 'DejaVu Sans Mono', monospace\" font-size=\"{SVG_FONT_SIZE}\" fill=\"{}\" xml:space=\"preserve\">",
+            Style::Plain.rgb()
+        )?;
+        for (i, line) in lines.iter().enumerate() {
+            let len = line_len(line);
+            if len == 0 {
+                continue;
+            }
+
+            // textLength pins every line to the cell grid, so the frame lines up with any font
+            let y = IMAGE_PADDING + i * SVG_LINE_HEIGHT + SVG_FONT_SIZE;
+            write!(
+                w,
+                "<tspan x=\"{IMAGE_PADDING}\" y=\"{y}\" textLength=\"{:.1}\" lengthAdjust=\"spacingAndGlyphs\">",
+                len as f64 * SVG_CHAR_WIDTH
+            )?;
+            for span in line {
+                let text = html_escape(&span.text);
+                if span.style == Style::Plain {
+                    write!(w, "{text}")?;
+                } else {
+                    write!(w, "<tspan fill=\"{}\">{text}</tspan>", span.style.rgb())?;
+                }
+            }
+            writeln!(w, "</tspan>")?;
+        }
+        writeln!(w, "</text>")?;
+        writeln!(w, "</svg>")
+    }
+
+    pub fn write_png(&self, w: &mut dyn Write) -> Result<(), Error> {
+        let lines = self.styled_lines();
+        let columns = lines.iter().map(line_len).max().unwrap_or(0);
+        let mut canvas = Canvas::new(
+            columns * PNG_CHAR_WIDTH + 2 * IMAGE_PADDING,
+            lines.len() * PNG_LINE_HEIGHT + 2 * IMAGE_PADDING,
+            parse_rgb(BACKGROUND),
+        )?;
+
+        for (i, line) in lines.iter().enumerate() {
+            let y = IMAGE_PADDING + i * PNG_LINE_HEIGHT;
+            let mut x = IMAGE_PADDING;
+            for span in line {
+                let color = parse_rgb(span.style.rgb());
+                for c in span.text.chars() {
+                    canvas.draw_char(c, x, y, color);
+                    x += PNG_CHAR_WIDTH;
+                }
+            }
+        }
+
+        let png_data = canvas.encode_png().map_err(io::Error::other)?;
+        w.write_all(&png_data)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rgb_test() {
+        assert_eq!(parse_rgb("#1e1e1e"), Rgb888::new(0x1e, 0x1e, 0x1e));
+        assert_eq!(parse_rgb("#3b8eea"), Rgb888::new(0x3b, 0x8e, 0xea));
+    }
+
+    #[test]
+    fn draw_char_test() {
+        let background = Rgb888::new(0, 0, 0);
+        let white = Rgb888::new(255, 255, 255);
+        let mut canvas = Canvas::new(PNG_CHAR_WIDTH, PNG_LINE_HEIGHT, background).unwrap();
+        canvas.draw_char('─', 0, 0, white);
+
+        // the horizontal line goes through the whole cell at half height
+        let row = PNG_LINE_HEIGHT / 2;
+        for x in 0..PNG_CHAR_WIDTH {
+            let i = (row * PNG_CHAR_WIDTH + x) * 3;
+            assert_eq!(canvas.pixels[i..i + 3], [255, 255, 255]);
+        }
+        assert_eq!(canvas.pixels[0..3], [0, 0, 0]);
+
+        assert!(matches!(
+            Canvas::new(1 << 16, 1 << 16, background),
+            Err(Error::PngTooLarge { .. })
+        ));
+    }
+}
@@ -0,0 +1,159 @@
+use super::Dump;
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+
+// quotes and escapes str so it can be used as a JSON string
+fn json_string(str: &str) -> String {
+    let mut out = String::with_capacity(str.len() + 2);
+    out.push('"');
+    for c in str.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl Dump {
+    // key value pairs describing the file and the resolved dump range
+    fn json_metadata(&self) -> Vec<(&'static str, String)> {
+        let mut metadata = vec![
+            (
+                "file",
+                self.path.as_deref().map_or("null".to_string(), json_string),
+            ),
+            (
+                "filesize",
+                self.input_filesize()
+                    .map_or("null".to_string(), |s| s.to_string()),
+            ),
+            (
+                "filesize_text",
+                self.input_filesize().map_or("null".to_string(), |s| {
+                    json_string(&Self::format_filesize(s))
+                }),
+            ),
+            ("addr_mode", json_string(self.addr_mode.name())),
+            ("start_addr", self.start_addr.to_string()),
+            (
+                "end_addr",
+                self.last_addr()
+                    .map_or("null".to_string(), |e| e.to_string()),
+            ),
+            ("num_bytes", self.dumped_bytes().to_string()),
+        ];
+        if !self.skips.is_empty() {
+            let ranges: Vec<String> = self
+                .dumped_ranges()
+                .iter()
+                .map(|r| format!("[{},{}]", r.start, r.end))
+                .collect();
+            metadata.push(("ranges", format!("[{}]", ranges.join(","))));
+        }
+        if !self.holes.is_empty() {
+            let holes: Vec<String> = self
+                .holes
+                .iter()
+                .map(|(hole, _)| format!("[{},{}]", hole.start, hole.end))
+                .collect();
+            metadata.push(("holes", format!("[{}]", holes.join(","))));
+        }
+        if !self.hashes.is_empty() {
+            let range = self.start_addr..self.start_addr + self.num_bytes;
+            let hashes: Vec<String> = self
+                .hashes
+                .iter()
+                .map(|algo| {
+                    let hash = self.hash(*algo, range.clone());
+                    format!("{}:{}", json_string(algo.name()), json_string(&hash))
+                })
+                .collect();
+            metadata.push(("hashes", format!("{{{}}}", hashes.join(","))));
+        }
+        if let Some((format, compressed_size)) = self.compression {
+            metadata.push(("compression", json_string(format.name())));
+            metadata.push(("compressed_size", compressed_size.to_string()));
+        }
+        metadata
+    }
+
+    // one row of the dump as JSON object, only contains the bytes of range
+    fn json_row(&self, range: RangeInclusive<u64>) -> String {
+        let bytes: Vec<Option<u8>> = range.clone().map(|addr| self.byte(addr)).collect();
+
+        // bytes in gaps of the address space are null
+        let numbers: Vec<String> = bytes
+            .iter()
+            .map(|b| b.map_or("null".to_string(), |b| b.to_string()))
+            .collect();
+        let ascii: String = bytes
+            .iter()
+            .map(|b| b.map_or(' ', Self::ascii_char))
+            .collect();
+
+        // checksum of the block that ends in the row (--block-hash)
+        let checksum = match self.block_checksum(*range.end()) {
+            Some(checksum) => format!(",\"block_hash\":{}", json_string(&checksum)),
+            None => String::new(),
+        };
+
+        format!(
+            "{{\"address\":{},\"bytes\":[{}],\"ascii\":{}{checksum}}}",
+            range.start(),
+            numbers.join(","),
+            json_string(&ascii)
+        )
+    }
+
+    pub fn write_json(&self, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "{{")?;
+        for (key, value) in self.json_metadata() {
+            writeln!(w, "  \"{key}\": {value},")?;
+        }
+
+        writeln!(w, "  \"rows\": [")?;
+        let mut rows = self.row_parts().peekable();
+        while let Some(range) = rows.next() {
+            let separator = if rows.peek().is_some() { "," } else { "" };
+            writeln!(w, "    {}{}", self.json_row(range), separator)?;
+        }
+        writeln!(w, "  ]")?;
+        writeln!(w, "}}")
+    }
+
+    pub fn write_json_lines(&self, w: &mut dyn Write) -> io::Result<()> {
+        // first line holds the metadata, every following line one row
+        let members: Vec<String> = self
+            .json_metadata()
+            .iter()
+            .map(|(key, value)| format!("\"{key}\":{value}"))
+            .collect();
+        writeln!(w, "{{\"type\":\"header\",{}}}", members.join(","))?;
+
+        for range in self.row_parts() {
+            let row = self.json_row(range);
+            writeln!(w, "{{\"type\":\"row\",{}", &row[1..])?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_string_test() {
+        assert_eq!(json_string("abc"), "\"abc\"");
+        assert_eq!(json_string("a\"b\\c"), "\"a\\\"b\\\\c\"");
+        assert_eq!(json_string("C:\\dir\n"), "\"C:\\\\dir\\n\"");
+        assert_eq!(json_string("\u{1}"), "\"\\u0001\"");
+    }
+}
@@ -0,0 +1,127 @@
+use super::encoding::Cell;
+use super::{Dump, Encoding};
+use crate::color::byte_color;
+use std::io::{self, Write};
+use unicode_width::UnicodeWidthChar;
+
+// wraps text into a code span that can be used inside of a table cell
+fn md_code(text: &str) -> String {
+    let text = text.replace('|', "\\|");
+    if text.contains('`') {
+        format!("`` {text} ``")
+    } else {
+        format!("`{text}`")
+    }
+}
+
+impl Dump {
+    pub fn write_markdown(&self, w: &mut dyn Write) -> io::Result<()> {
+        // the plain frame inside of a fenced code block (code blocks can't hold highlights)
+        writeln!(w, "```text")?;
+        for line in self.styled_lines() {
+            let text: String = line.iter().map(|span| span.text.as_str()).collect();
+            writeln!(w, "{text}")?;
+        }
+        writeln!(w, "```")
+    }
+
+    pub fn write_markdown_table(&self, w: &mut dyn Write) -> io::Result<()> {
+        let hex_width = self.hex_width();
+        if let Some(path_line) = self.path_line() {
+            writeln!(w, "{path_line}\\")?;
+        }
+        writeln!(w, "{}\\", self.filesize_line())?;
+        write!(w, "{}", self.dumping_line(hex_width))?;
+        for hash_line in self.hash_lines() {
+            write!(w, "\\\n{hash_line}")?;
+        }
+        writeln!(w)?;
+        writeln!(w)?;
+        // the checksum column of --block-hash
+        let (column, column_align) = match self.block_hash {
+            Some((algo, _)) => (format!(" {} |", algo.title()), ":------|"),
+            None => (String::new(), ""),
+        };
+        writeln!(w, "| Offset | Hex | {} |{column}", self.encoding.title())?;
+        writeln!(w, "|:------:|:----|:------|{column_align}")?;
+
+        for range in self.row_parts() {
+            let bytes: Vec<Option<u8>> = range.clone().map(|addr| self.byte(addr)).collect();
+
+            // highlighted bytes are bold
+            let hex: Vec<String> = bytes
+                .iter()
+                .map(|&byte| match byte {
+                    Some(data) if byte_color(data, self.visu_mode).is_some() => {
+                        format!("**{data:02X}**")
+                    }
+                    Some(data) => format!("{data:02X}"),
+                    None => "--".to_string(),
+                })
+                .collect();
+            let text: String = if self.encoding == Encoding::Ascii {
+                bytes
+                    .iter()
+                    .map(|b| b.map_or(' ', Self::ascii_char))
+                    .collect()
+            } else {
+                let mut cells = vec![Cell::Blank; bytes.len()];
+                self.text_cells(*range.start(), &mut cells);
+                cells
+                    .iter()
+                    .map(|cell| match *cell {
+                        Cell::Blank => " ".to_string(),
+                        Cell::Dot => ".".to_string(),
+                        Cell::Char(c, span) => {
+                            format!("{c}{}", " ".repeat(span - c.width().unwrap_or(1)))
+                        }
+                        Cell::Spanned => String::new(),
+                    })
+                    .collect()
+            };
+
+            write!(
+                w,
+                "| `{:0width$X}` | {} | {} |",
+                range.start(),
+                hex.join(" "),
+                md_code(&text),
+                width = hex_width
+            )?;
+            if self.block_hash.is_some() {
+                let checksum = self.block_checksum(*range.end());
+                write!(w, " {} |", checksum.map_or(String::new(), |c| md_code(&c)))?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HashAlgo;
+
+    #[test]
+    fn md_code_test() {
+        assert_eq!(md_code("Hello"), "`Hello`");
+        assert_eq!(md_code("a|b"), "`a\\|b`");
+        assert_eq!(md_code("a`b"), "`` a`b ``");
+    }
+
+    #[test]
+    fn markdown_table_block_hash_test() {
+        let mut out = Vec::new();
+        Dump::from_bytes(vec![0; 32])
+            .block_hash(HashAlgo::Crc32, 32)
+            .write_markdown_table(&mut out)
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines[3].ends_with("| ASCII | CRC-32 |"));
+        assert!(lines[5].ends_with("|  |"));
+        let crc = Dump::from_bytes(vec![0; 32]).hash(HashAlgo::Crc32, 0..32);
+        assert!(lines[6].ends_with(&format!("| `{crc}` |")));
+    }
+}
@@ -0,0 +1,280 @@
+use super::{Dump, gap_layout};
+use crate::Error;
+use std::fs;
+use std::io;
+use std::ops::Range;
+
+// Memory of a running process (linux): the mappings listed in /proc/<pid>/maps select the
+// address range, only the dumped range is read from /proc/<pid>/mem once the range is set
+
+const PAGE_SIZE: u64 = 4096;
+
+// a line of /proc/<pid>/maps
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Mapping {
+    pub range: Range<u64>,
+    pub perms: String,
+    pub name: String, // path or pseudo name like [heap], empty for anonymous mappings
+}
+
+impl Mapping {
+    // text of the note row shown above the first row of the mapping
+    pub fn note(&self, hex_width: usize) -> String {
+        format!(
+            "{:0width$X} to {:0width$X} {} {}",
+            self.range.start,
+            self.range.end - 1,
+            self.perms,
+            self.name,
+            width = hex_width
+        )
+        .trim_end()
+        .to_string()
+    }
+
+    // --map name matches pseudo names without brackets (heap) and parts of paths (libc)
+    fn matches(&self, name: &str) -> bool {
+        self.name == format!("[{name}]") || (!name.is_empty() && self.name.contains(name))
+    }
+}
+
+pub(super) struct Process {
+    pid: u32,
+    comm: String,
+    pub mappings: Vec<Mapping>, // the contiguous mappings the dump lies in
+    unreadable: u64,            // bytes of the dump range that couldn't be read
+    pub loaded: bool,           // the dump range was read
+}
+
+impl Process {
+    // the Process: line of the dump header (instead of the Filesize: line)
+    pub fn header_line(&self) -> String {
+        let mut line = format!("Process: {} ({})", self.pid, self.comm);
+        if self.unreadable > 0 {
+            line += &format!(", {} bytes unreadable (shown as --)", self.unreadable);
+        }
+        line
+    }
+}
+
+fn parse_maps(text: &str) -> Result<Vec<Mapping>, String> {
+    let mut mappings = Vec::new();
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let mut fields = line.split_whitespace();
+        let (Some(range), Some(perms)) = (fields.next(), fields.next()) else {
+            return Err(format!("invalid line '{line}'"));
+        };
+        let range = range
+            .split_once('-')
+            .and_then(|(start, end)| {
+                let start = u64::from_str_radix(start, 16).ok()?;
+                let end = u64::from_str_radix(end, 16).ok()?;
+                Some(start..end)
+            })
+            .ok_or(format!("invalid address range in '{line}'"))?;
+        // offset, device and inode come before the name
+        let name: Vec<&str> = fields.skip(3).collect();
+        mappings.push(Mapping {
+            range,
+            perms: perms.to_string(),
+            name: name.join(" "),
+        });
+    }
+    Ok(mappings)
+}
+
+// the mapping at index and the mappings directly before and after it that pass keep
+fn contiguous(mappings: &[Mapping], index: usize, keep: impl Fn(&Mapping) -> bool) -> Range<usize> {
+    let mut first = index;
+    while first > 0
+        && mappings[first - 1].range.end == mappings[first].range.start
+        && keep(&mappings[first - 1])
+    {
+        first -= 1;
+    }
+    let mut end = index + 1;
+    while end < mappings.len()
+        && mappings[end - 1].range.end == mappings[end].range.start
+        && keep(&mappings[end])
+    {
+        end += 1;
+    }
+    first..end
+}
+
+#[cfg(target_os = "linux")]
+fn read_mem(pid: u32, range: Range<u64>) -> Result<(Vec<u8>, Vec<Range<u64>>), Error> {
+    use std::os::unix::fs::FileExt;
+
+    let path = format!("/proc/{pid}/mem");
+    let mem = fs::File::open(&path).map_err(|source| Error::Read { path, source })?;
+    let mut data = vec![0; (range.end - range.start) as usize];
+    if mem.read_exact_at(&mut data, range.start).is_ok() {
+        return Ok((data, Vec::new()));
+    }
+
+    // read page by page, the pages that can't be read become gaps and are left out of data
+    data.clear();
+    let mut page = [0; PAGE_SIZE as usize];
+    let mut gaps: Vec<Range<u64>> = Vec::new();
+    let mut addr = range.start;
+    while addr < range.end {
+        let end = ((addr / PAGE_SIZE + 1) * PAGE_SIZE).min(range.end);
+        let offset = addr - range.start;
+        let buf = &mut page[..(end - addr) as usize];
+        if mem.read_exact_at(buf, addr).is_ok() {
+            data.extend_from_slice(buf);
+        } else {
+            match gaps.last_mut() {
+                Some(gap) if gap.end == offset => gap.end = end - range.start,
+                _ => gaps.push(offset..end - range.start),
+            }
+        }
+        addr = end;
+    }
+    data.shrink_to_fit();
+    Ok((data, gaps))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_mem(pid: u32, _range: Range<u64>) -> Result<(Vec<u8>, Vec<Range<u64>>), Error> {
+    Err(Error::Read {
+        path: format!("/proc/{pid}/mem"),
+        source: io::Error::new(
+            io::ErrorKind::Unsupported,
+            "process memory can only be dumped on linux",
+        ),
+    })
+}
+
+impl Dump {
+    /// Creates a dump of the memory of the process pid, the addresses are the virtual addresses
+    /// of the process. The dump lies in the first mapping whose name matches map (eg: heap,
+    /// stack, libc) or the mapping that contains addr, together with the mappings directly
+    /// following and preceding it. The memory is read by [`Dump::load`] once the range is
+    /// set, unreadable pages are shown as gaps.
+    pub fn process(pid: u32, map: Option<&str>, addr: Option<u64>) -> Result<Dump, Error> {
+        let maps_path = format!("/proc/{pid}/maps");
+        let read_error = |path: &str, source| Error::Read {
+            path: path.to_string(),
+            source,
+        };
+        let text = fs::read_to_string(&maps_path).map_err(|e| read_error(&maps_path, e))?;
+        let mappings = parse_maps(&text).map_err(|message| Error::Decode {
+            format: "maps",
+            message,
+        })?;
+        let comm_path = format!("/proc/{pid}/comm");
+        let comm = fs::read_to_string(&comm_path).map_err(|e| read_error(&comm_path, e))?;
+
+        let not_found = |message: String| {
+            read_error(&maps_path, io::Error::new(io::ErrorKind::NotFound, message))
+        };
+        let selected = match (map, addr) {
+            (Some(map), _) => {
+                let index = mappings
+                    .iter()
+                    .position(|m| m.matches(map))
+                    .ok_or_else(|| not_found(format!("no mapping matches {map}")))?;
+                contiguous(&mappings, index, |m| m.matches(map))
+            }
+            (None, Some(addr)) => {
+                let index = mappings
+                    .iter()
+                    .position(|m| m.range.contains(&addr))
+                    .ok_or_else(|| not_found(format!("no mapping contains {addr:X}")))?;
+                contiguous(&mappings, index, |_| true)
+            }
+            (None, None) => return Err(Error::ProcessNeedsAddress),
+        };
+        let mappings = mappings[selected].to_vec();
+
+        // the data is read once the range is known, until then the dump only spans the mappings
+        let start = mappings[0].range.start;
+        let end = mappings[mappings.len() - 1].range.end;
+        let mut dump = Dump::from_layout(Vec::new(), start, Vec::new());
+        dump.filesize = end - start;
+        dump.file_end = end;
+        dump.num_bytes = end - start;
+        dump.path = Some(format!("/proc/{pid}/mem"));
+        dump.process = Some(Process {
+            pid,
+            comm: comm.trim_end().to_string(),
+            mappings,
+            unreadable: 0,
+            loaded: false,
+        });
+        Ok(dump)
+    }
+
+    // reads the dump range of the process memory, the data then only holds the dump range
+    pub(super) fn load_process(&mut self) -> Result<(), Error> {
+        let Some(process) = self.process.as_mut().filter(|p| !p.loaded) else {
+            return Ok(());
+        };
+        let range = self.start_addr..self.start_addr + self.num_bytes;
+        let (data, gaps) = read_mem(process.pid, range.clone())?;
+        process.unreadable = gaps.iter().map(|gap| gap.end - gap.start).sum();
+        process.loaded = true;
+
+        self.data = data;
+        self.gaps = gap_layout(gaps);
+        self.base_addr = range.start;
+        self.file_end = range.end;
+        self.filesize = range.end - range.start;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAPS: &str = "\
+55d4c8a3e000-55d4c8a5f000 rw-p 00000000 00:00 0                          [heap]
+7f3a1c000000-7f3a1c028000 r--p 00000000 08:01 1234                       /usr/lib/libc.so.6
+7f3a1c028000-7f3a1c1bd000 r-xp 00028000 08:01 1234                       /usr/lib/libc.so.6
+7f3a1c1bd000-7f3a1c215000 r--p 001bd000 08:01 1234                       /usr/lib/libc.so.6
+7f3a1c215000-7f3a1c216000 rw-p 00000000 00:00 0
+7ffd5a3c1000-7ffd5a3e2000 rw-p 00000000 00:00 0                          [stack]
+";
+
+    #[test]
+    fn parse_maps_test() {
+        let mappings = parse_maps(MAPS).unwrap();
+        assert_eq!(mappings.len(), 6);
+        assert_eq!(
+            mappings[0],
+            Mapping {
+                range: 0x55d4c8a3e000..0x55d4c8a5f000,
+                perms: "rw-p".to_string(),
+                name: "[heap]".to_string(),
+            }
+        );
+        assert_eq!(mappings[4].name, "");
+        assert!(mappings[0].matches("heap") && mappings[5].matches("stack"));
+        assert!(parse_maps("zz-10 rw-p").is_err());
+    }
+
+    #[test]
+    fn contiguous_test() {
+        let mappings = parse_maps(MAPS).unwrap();
+        assert_eq!(contiguous(&mappings, 2, |m| m.matches("libc")), 1..4);
+        assert_eq!(contiguous(&mappings, 2, |_| true), 1..5);
+        assert_eq!(contiguous(&mappings, 0, |_| true), 0..1);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn load_test() {
+        let bytes = std::hint::black_box([0x5A_u8; 64]);
+        let addr = bytes.as_ptr() as u64;
+        let mut dump = Dump::process(std::process::id(), None, Some(addr))
+            .unwrap()
+            .range(addr..addr + 64);
+        assert!(dump.write_to(io::sink()).is_err());
+        dump.load().unwrap();
+        assert_eq!(dump.data, bytes);
+        assert!(dump.write_to(io::sink()).is_ok());
+    }
+}
@@ -0,0 +1,389 @@
+use super::Dump;
+use crate::Error;
+use std::io::Write;
+use std::ops::Range;
+
+// Intel HEX and Motorola S-record files, both store data records at addresses of a (sparse) address space
+
+// bytes stored at addr, as read from one or more data records
+#[derive(Debug, PartialEq)]
+pub struct Chunk {
+    pub addr: u64,
+    pub data: Vec<u8>,
+}
+
+// decodes a string of hex digit pairs
+fn hex_bytes(str: &str) -> Option<Vec<u8>> {
+    if !str.len().is_multiple_of(2) || !str.is_ascii() {
+        return None;
+    }
+    (0..str.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&str[i..i + 2], 16).ok())
+        .collect()
+}
+
+fn be_value(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0, |value, &b| (value << 8) | b as u64)
+}
+
+pub fn parse_ihex(text: &str) -> Result<Vec<Chunk>, String> {
+    let mut chunks = Vec::new();
+    let mut base: u64 = 0;
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let err = |msg: &str| format!("line {}: {msg}", i + 1);
+
+        let bytes = line
+            .strip_prefix(':')
+            .and_then(hex_bytes)
+            .ok_or_else(|| err("not an Intel HEX record"))?;
+        if bytes.len() < 5 || bytes.len() != bytes[0] as usize + 5 {
+            return Err(err("invalid record length"));
+        }
+        if bytes.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) != 0 {
+            return Err(err("invalid checksum"));
+        }
+
+        let offset = be_value(&bytes[1..3]);
+        let data = &bytes[4..bytes.len() - 1];
+        match bytes[3] {
+            // data
+            0x00 => chunks.push(Chunk {
+                addr: base + offset,
+                data: data.to_vec(),
+            }),
+            // end of file
+            0x01 => break,
+            // extended segment address
+            0x02 if data.len() == 2 => base = be_value(data) << 4,
+            // extended linear address
+            0x04 if data.len() == 2 => base = be_value(data) << 16,
+            // start segment / linear address (no data)
+            0x03 | 0x05 => {}
+            _ => return Err(err("invalid record type")),
+        }
+    }
+
+    if chunks.is_empty() {
+        return Err("no data records".to_string());
+    }
+    Ok(chunks)
+}
+
+pub fn parse_srec(text: &str) -> Result<Vec<Chunk>, String> {
+    let mut chunks = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let err = |msg: &str| format!("line {}: {msg}", i + 1);
+
+        let (kind, bytes) = match line.as_bytes() {
+            [b'S', kind, ..] if kind.is_ascii_digit() => (kind - b'0', hex_bytes(&line[2..])),
+            _ => (0, None),
+        };
+        let bytes = bytes.ok_or_else(|| err("not an S-record"))?;
+        let addr_len = match kind {
+            0 | 1 | 5 | 9 => 2,
+            2 | 6 | 8 => 3,
+            3 | 7 => 4,
+            _ => return Err(err("invalid record type")),
+        };
+        if bytes.len() < addr_len + 2 || bytes.len() != bytes[0] as usize + 1 {
+            return Err(err("invalid record length"));
+        }
+        if bytes.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) != 0xFF {
+            return Err(err("invalid checksum"));
+        }
+
+        if let 1..=3 = kind {
+            chunks.push(Chunk {
+                addr: be_value(&bytes[1..1 + addr_len]),
+                data: bytes[1 + addr_len..bytes.len() - 1].to_vec(),
+            });
+        }
+    }
+
+    if chunks.is_empty() {
+        return Err("no data records".to_string());
+    }
+    Ok(chunks)
+}
+
+// picks the record format by the first record of the text, None if data isn't a record file
+pub fn detect(data: &[u8]) -> Option<Vec<Chunk>> {
+    let text = std::str::from_utf8(data).ok()?;
+    let first = text.lines().map(str::trim).find(|l| !l.is_empty())?;
+    if first.starts_with(':') {
+        parse_ihex(text).ok()
+    } else if first.starts_with('S') {
+        parse_srec(text).ok()
+    } else {
+        None
+    }
+}
+
+// lays out the set bytes of the chunks in a flat buffer, returns (lowest address, buffer,
+// gaps between the chunks as offsets from the lowest address), the gap bytes aren't in the buffer
+pub fn flatten(chunks: &[Chunk]) -> (u64, Vec<u8>, Vec<Range<u64>>) {
+    let base = chunks.iter().map(|c| c.addr).min().unwrap_or(0);
+
+    // the covered parts of the span, merged, with the gap bytes before each of them
+    let mut set: Vec<Range<u64>> = chunks
+        .iter()
+        .map(|c| c.addr - base..c.addr - base + c.data.len() as u64)
+        .collect();
+    set.sort_by_key(|r| r.start);
+    let mut covered: Vec<(Range<u64>, u64)> = Vec::new();
+    let mut gaps = Vec::new();
+    for range in set {
+        match covered.last_mut() {
+            Some((last, _)) if range.start <= last.end => last.end = last.end.max(range.end),
+            last => {
+                // everything not covered by a chunk is a gap
+                let end = last.map_or(0, |(last, _)| last.end);
+                if range.start > end {
+                    gaps.push(end..range.start);
+                }
+                let before = gaps.iter().map(|gap| gap.end - gap.start).sum();
+                covered.push((range, before));
+            }
+        }
+    }
+
+    let len = covered
+        .last()
+        .map_or(0, |(range, before)| range.end - before);
+    let mut data = vec![0; len as usize];
+    for chunk in chunks {
+        let offset = chunk.addr - base;
+        let (_, before) = &covered[covered.partition_point(|(r, _)| r.start <= offset) - 1];
+        let start = (offset - before) as usize;
+        data[start..start + chunk.data.len()].copy_from_slice(&chunk.data);
+    }
+
+    (base, data, gaps)
+}
+
+fn ihex_record(kind: u8, offset: u16, data: &[u8]) -> String {
+    let mut bytes = vec![data.len() as u8, (offset >> 8) as u8, offset as u8, kind];
+    bytes.extend_from_slice(data);
+    let sum = bytes.iter().fold(0u8, |sum, &b| sum.wrapping_add(b));
+    bytes.push(sum.wrapping_neg());
+
+    let hex: String = bytes.iter().map(|b| format!("{b:02X}")).collect();
+    format!(":{hex}")
+}
+
+pub fn ihex_records(chunks: &[Chunk]) -> Vec<String> {
+    let mut records = Vec::new();
+    let mut upper = 0;
+
+    for chunk in chunks {
+        let mut addr = chunk.addr;
+        let mut data = chunk.data.as_slice();
+        while !data.is_empty() {
+            if addr >> 16 != upper {
+                upper = addr >> 16;
+                records.push(ihex_record(0x04, 0, &(upper as u16).to_be_bytes()));
+            }
+
+            // records must not cross a 64k boundary
+            let len = data.len().min(16).min((0x10000 - (addr & 0xFFFF)) as usize);
+            records.push(ihex_record(0x00, addr as u16, &data[..len]));
+            addr += len as u64;
+            data = &data[len..];
+        }
+    }
+    records.push(ihex_record(0x01, 0, &[]));
+    records
+}
+
+fn srec_record(kind: u8, addr: u64, addr_len: usize, data: &[u8]) -> String {
+    let mut bytes = vec![(addr_len + data.len() + 1) as u8];
+    bytes.extend_from_slice(&addr.to_be_bytes()[8 - addr_len..]);
+    bytes.extend_from_slice(data);
+    let sum = bytes.iter().fold(0u8, |sum, &b| sum.wrapping_add(b));
+    bytes.push(!sum);
+
+    let hex: String = bytes.iter().map(|b| format!("{b:02X}")).collect();
+    format!("S{kind}{hex}")
+}
+
+pub fn srec_records(chunks: &[Chunk]) -> Vec<String> {
+    // the smallest address size that can hold all addresses
+    let end = chunks
+        .iter()
+        .map(|c| c.addr + c.data.len() as u64)
+        .max()
+        .unwrap_or(0);
+    let (data_kind, addr_len) = if end <= 0x10000 {
+        (1, 2)
+    } else if end <= 0x1000000 {
+        (2, 3)
+    } else {
+        (3, 4)
+    };
+
+    let mut records = vec![srec_record(0, 0, 2, b"hexdump")];
+    let mut count: u64 = 0;
+    for chunk in chunks {
+        for (i, data) in chunk.data.chunks(16).enumerate() {
+            records.push(srec_record(
+                data_kind,
+                chunk.addr + i as u64 * 16,
+                addr_len,
+                data,
+            ));
+            count += 1;
+        }
+    }
+    if count <= 0xFFFF {
+        records.push(srec_record(5, count, 2, &[]));
+    } else {
+        records.push(srec_record(6, count, 3, &[]));
+    }
+    records.push(srec_record(10 - data_kind, 0, addr_len, &[]));
+    records
+}
+
+impl Dump {
+    // the set bytes of the dump range, split at the gaps
+    fn range_chunks(&self) -> Vec<Chunk> {
+        let mut chunks: Vec<Chunk> = Vec::new();
+        for addr in self.dumped_ranges().into_iter().flatten() {
+            match (self.byte(addr), chunks.last_mut()) {
+                (Some(b), Some(last)) if last.addr + last.data.len() as u64 == addr => {
+                    last.data.push(b)
+                }
+                (Some(b), _) => chunks.push(Chunk {
+                    addr,
+                    data: vec![b],
+                }),
+                (None, _) => {}
+            }
+        }
+        chunks
+    }
+
+    fn write_records(
+        &self,
+        w: &mut dyn Write,
+        records: fn(&[Chunk]) -> Vec<String>,
+    ) -> Result<(), Error> {
+        if let Some(end) = self.last_addr()
+            && end > u32::MAX as u64
+        {
+            return Err(Error::RecordAddressSpace { end });
+        }
+        for record in records(&self.range_chunks()) {
+            writeln!(w, "{record}")?;
+        }
+        Ok(())
+    }
+
+    pub fn write_ihex(&self, w: &mut dyn Write) -> Result<(), Error> {
+        self.write_records(w, ihex_records)
+    }
+
+    pub fn write_srec(&self, w: &mut dyn Write) -> Result<(), Error> {
+        self.write_records(w, srec_records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ihex_test() {
+        let text = ":020000040800F2\n:0400100001020304E2\n:00000001FF\n";
+        assert_eq!(
+            parse_ihex(text),
+            Ok(vec![Chunk {
+                addr: 0x08000010,
+                data: vec![1, 2, 3, 4]
+            }])
+        );
+        assert_eq!(
+            parse_ihex(":0400100001020304E3\n"),
+            Err("line 1: invalid checksum".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_srec_test() {
+        let text = "S00600004844521B\nS107001001020304DE\nS9030000FC\n";
+        assert_eq!(
+            parse_srec(text),
+            Ok(vec![Chunk {
+                addr: 0x10,
+                data: vec![1, 2, 3, 4]
+            }])
+        );
+        assert!(parse_srec("S107001001020304DF\n").is_err());
+    }
+
+    #[test]
+    fn records_roundtrip_test() {
+        let chunks = vec![
+            Chunk {
+                addr: 0xFFF8,
+                data: (0..20).collect(),
+            },
+            Chunk {
+                addr: 0x08000000,
+                data: vec![0xAA; 3],
+            },
+        ];
+        let ihex = ihex_records(&chunks).join("\n");
+        let srec = srec_records(&chunks).join("\n");
+        assert_eq!(flatten(&parse_ihex(&ihex).unwrap()), flatten(&chunks));
+        assert_eq!(flatten(&parse_srec(&srec).unwrap()), flatten(&chunks));
+    }
+
+    #[test]
+    fn flatten_test() {
+        let chunks = vec![
+            Chunk {
+                addr: 0x100,
+                data: vec![1, 2],
+            },
+            Chunk {
+                addr: 0x104,
+                data: vec![3],
+            },
+            Chunk {
+                addr: 0x107,
+                data: vec![4],
+            },
+        ];
+        assert_eq!(
+            flatten(&chunks),
+            (0x100, vec![1, 2, 3, 4], vec![2..4, 5..7])
+        );
+
+        // chunks far apart don't allocate the gap between them
+        let chunks = vec![
+            Chunk {
+                addr: 0,
+                data: vec![1],
+            },
+            Chunk {
+                addr: 0xFFFF_0000,
+                data: vec![2, 3],
+            },
+        ];
+        let (base, data, gaps) = flatten(&chunks);
+        assert_eq!((base, data), (0, vec![1, 2, 3]));
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0], 1..0xFFFF_0000);
+    }
+}
@@ -0,0 +1,373 @@
+use super::Dump;
+use crate::Error;
+use colored::*;
+use crc::{CRC_32_ISO_HDLC, Crc};
+use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+
+// Scan mode (binwalk-lite): the dump range is searched for the magic numbers of known file
+// formats, the bytes after a magic number are checked to rule out random matches
+
+// bytes after a magic number that are looked at to identify the content
+const LOOKAHEAD: usize = 1024;
+
+// bytes that are searched at once, the last LOOKAHEAD bytes are searched with the next window
+const WINDOW: usize = 1 << 20;
+
+static CRC_32: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+
+// a magic number found by the scan
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Hit {
+    pub addr: u64,
+    pub magic_len: u64, // bytes of the magic number, they are marked in the dump
+    pub kind: &'static str,
+    pub description: String,
+}
+
+fn be_u16(b: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        b.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn le_u16(b: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        b.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn be_u32(b: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        b.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+// text of a NUL terminated or fixed size name field, None if it isn't printable
+fn name(b: &[u8]) -> Option<String> {
+    let end = b.iter().position(|&c| c == 0).unwrap_or(b.len());
+    let name = std::str::from_utf8(&b[..end]).ok()?;
+    (!name.is_empty() && name.chars().all(|c| !c.is_control())).then(|| name.to_string())
+}
+
+fn elf_machine(machine: u16) -> String {
+    let name = match machine {
+        3 => "x86",
+        8 => "MIPS",
+        20 => "PowerPC",
+        21 => "PowerPC64",
+        40 => "ARM",
+        42 => "SuperH",
+        62 => "x86-64",
+        94 => "Xtensa",
+        183 => "AArch64",
+        243 => "RISC-V",
+        _ => return format!("machine {machine}"),
+    };
+    name.to_string()
+}
+
+// the type, magic number length and description of the content b starts with
+fn identify(b: &[u8]) -> Option<(&'static str, usize, String)> {
+    match b.first()? {
+        0x7F if b.starts_with(b"\x7FELF") => {
+            let (bits, endian) = match (b.get(4)?, b.get(5)?, b.get(6)?) {
+                (1, 1, 1) => ("32-bit", "LSB"),
+                (2, 1, 1) => ("64-bit", "LSB"),
+                (1, 2, 1) => ("32-bit", "MSB"),
+                (2, 2, 1) => ("64-bit", "MSB"),
+                _ => return None,
+            };
+            let u16_at = if endian == "LSB" { le_u16 } else { be_u16 };
+            let kind = match u16_at(b, 16)? {
+                1 => "relocatable",
+                2 => "executable",
+                3 => "shared object",
+                4 => "core file",
+                _ => return None,
+            };
+            let machine = elf_machine(u16_at(b, 18)?);
+            Some(("ELF", 4, format!("{bits} {endian} {kind}, {machine}")))
+        }
+        b'P' if b.starts_with(b"PK\x03\x04") => {
+            let name_len = le_u16(b, 26)? as usize;
+            let name = name(b.get(30..30 + name_len)?)?;
+            (le_u16(b, 4)? <= 63).then(|| ("ZIP", 4, format!("archive entry, name: {name}")))
+        }
+        0x1F if b.starts_with(&[0x1F, 0x8B, 0x08]) => {
+            let flags = *b.get(3)?;
+            if flags & 0xE0 != 0 {
+                return None;
+            }
+            // the original name follows the optional extra field
+            let mut offset = 10;
+            if flags & 0x04 != 0 {
+                offset += 2 + le_u16(b, 10)? as usize;
+            }
+            let description = match flags & 0x08 {
+                0 => "compressed data".to_string(),
+                _ => format!("compressed data, name: {}", name(b.get(offset..)?)?),
+            };
+            Some(("gzip", 3, description))
+        }
+        b'B' if b.starts_with(b"BZh") && b.get(4..10)? == b"1AY&SY" => {
+            let level = *b.get(3)?;
+            level.is_ascii_digit().then(|| {
+                (
+                    "bzip2",
+                    3,
+                    format!("compressed data, {}00k blocks", level as char),
+                )
+            })
+        }
+        0xFD if b.starts_with(&[0xFD, b'7', b'z', b'X', b'Z', 0x00]) => {
+            Some(("xz", 6, "compressed data".to_string()))
+        }
+        0x28 if b.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) => {
+            Some(("zstd", 4, "compressed data".to_string()))
+        }
+        b'7' if b.starts_with(&[b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C]) => Some((
+            "7-Zip",
+            6,
+            format!("archive, version {}.{}", b.get(6)?, b.get(7)?),
+        )),
+        0x89 if b.starts_with(b"\x89PNG\r\n\x1A\n") && b.get(12..16)? == b"IHDR" => Some((
+            "PNG",
+            8,
+            format!("image, {} x {}", be_u32(b, 16)?, be_u32(b, 20)?),
+        )),
+        0xFF if b.starts_with(&[0xFF, 0xD8, 0xFF]) => {
+            let description = match (b.get(3)?, b.get(6..10)?) {
+                (0xE0, b"JFIF") => "image (JFIF)",
+                (0xE1, b"Exif") => "image (Exif)",
+                (0xE0..=0xEF | 0xDB | 0xFE, _) => "image",
+                _ => return None,
+            };
+            Some(("JPEG", 3, description.to_string()))
+        }
+        b'G' if b.starts_with(b"GIF87a") || b.starts_with(b"GIF89a") => Some((
+            "GIF",
+            6,
+            format!("image, {} x {}", le_u16(b, 6)?, le_u16(b, 8)?),
+        )),
+        b'%' if b.starts_with(b"%PDF-") => {
+            let version = b.get(5..8)?;
+            let version = std::str::from_utf8(version).ok()?;
+            Some(("PDF", 5, format!("document, version {version}")))
+        }
+        b'h' | b's' if b.starts_with(b"hsqs") || b.starts_with(b"sqsh") => {
+            // little endian images start with hsqs
+            let le = b[0] == b'h';
+            let u16_at = if le { le_u16 } else { be_u16 };
+            let (major, minor) = (u16_at(b, 28)?, u16_at(b, 30)?);
+            if !(1..=4).contains(&major) {
+                return None;
+            }
+            let bytes = b.get(40..48)?.try_into().ok()?;
+            let bytes = if le {
+                u64::from_le_bytes(bytes)
+            } else {
+                u64::from_be_bytes(bytes)
+            };
+            Some((
+                "squashfs",
+                4,
+                format!("filesystem v{major}.{minor}, {bytes} bytes"),
+            ))
+        }
+        0x27 if b.starts_with(&[0x27, 0x05, 0x19, 0x56]) => {
+            // the header checksum is computed with the checksum field set to 0
+            let mut header: [u8; 64] = b.get(..64)?.try_into().ok()?;
+            let checksum = be_u32(&header, 4)?;
+            header[4..8].fill(0);
+            if CRC_32.checksum(&header) != checksum {
+                return None;
+            }
+            let compression = match header[31] {
+                0 => "",
+                1 => ", gzip compressed",
+                2 => ", bzip2 compressed",
+                3 => ", lzma compressed",
+                4 => ", lzo compressed",
+                5 => ", lz4 compressed",
+                6 => ", zstd compressed",
+                _ => ", unknown compression",
+            };
+            let name = name(&header[32..]).unwrap_or_default();
+            let size = be_u32(&header, 12)?;
+            Some(("uImage", 4, format!("'{name}', {size} bytes{compression}")))
+        }
+        0xD0 if b.starts_with(&[0xD0, 0x0D, 0xFE, 0xED]) => {
+            let (size, version, compatible) = (be_u32(b, 4)?, be_u32(b, 20)?, be_u32(b, 24)?);
+            ((1..=17).contains(&version) && compatible <= version && size >= 40).then(|| {
+                (
+                    "DTB",
+                    4,
+                    format!("device tree blob v{version}, {size} bytes"),
+                )
+            })
+        }
+        b'0' if b.starts_with(b"07070") && matches!(b.get(5)?, b'1' | b'2') => {
+            let fields = b.get(6..110)?;
+            fields
+                .iter()
+                .all(u8::is_ascii_hexdigit)
+                .then(|| ("cpio", 6, "archive (newc)".to_string()))
+        }
+        _ => None,
+    }
+}
+
+// adds the hits that start in buf[..end] (buf starts at addr) to hits
+fn scan(buf: &[u8], end: usize, addr: u64, hits: &mut Vec<Hit>) {
+    for pos in 0..end {
+        let rest = &buf[pos..buf.len().min(pos + LOOKAHEAD)];
+        if let Some((kind, magic_len, description)) = identify(rest) {
+            hits.push(Hit {
+                addr: addr + pos as u64,
+                magic_len: magic_len as u64,
+                kind,
+                description,
+            });
+        }
+    }
+}
+
+impl Dump {
+    // the magic numbers in the dumped bytes, sorted by address
+    pub(super) fn scan_hits(&self) -> Vec<Hit> {
+        let mut hits = Vec::new();
+        for range in self.dumped_ranges() {
+            self.scan_range(range, &mut hits);
+        }
+        hits
+    }
+
+    // scans range window by window (the bytes of holes and gaps are zeros)
+    fn scan_range(&self, range: Range<u64>, hits: &mut Vec<Hit>) {
+        let mut buf = Vec::new();
+        let mut buf_addr = range.start; // address of buf[0]
+        self.visit_slices(range, Some(0), &mut |slice| {
+            buf.extend_from_slice(slice);
+            if buf.len() >= WINDOW + LOOKAHEAD {
+                let end = buf.len() - LOOKAHEAD;
+                scan(&buf, end, buf_addr, hits);
+                buf.drain(..end);
+                buf_addr += end as u64;
+            }
+        });
+        scan(&buf, buf.len(), buf_addr, hits);
+    }
+
+    // the note row above the row a hit starts in (--mark-signatures)
+    pub(super) fn hit_text(hit: &Hit, hex_width: usize) -> String {
+        format!(
+            "{} at {:0width$X}: {}",
+            hit.kind,
+            hit.addr,
+            hit.description,
+            width = hex_width
+        )
+    }
+
+    // returns if addr lies in the magic number of a hit (--mark-signatures)
+    pub(super) fn marked(&self, addr: u64) -> bool {
+        let i = self
+            .marks
+            .partition_point(|hit| hit.addr + hit.magic_len <= addr);
+        self.marks.get(i).is_some_and(|hit| hit.addr <= addr)
+    }
+
+    /// Writes a table of the known file formats found in the dumped bytes (eg: an ELF file,
+    /// a squashfs image or gzip data inside of a firmware image) instead of the dump. The
+    /// addresses are written as hex numbers that can be passed to -s.
+    pub fn write_scan(&self, mut w: impl Write) -> Result<(), Error> {
+        self.check_range()?;
+        let w: &mut dyn Write = &mut w;
+        if let Some(path_line) = self.path_line() {
+            writeln!(w, "{path_line}")?;
+        }
+        writeln!(w, "{}", self.filesize_line())?;
+        let hex_width = self.hex_width();
+        let dumping_line = self.dumping_line(hex_width);
+        writeln!(w, "{}", dumping_line.replacen("Dumping", "Scanning", 1))?;
+        writeln!(w)?;
+
+        let hits = self.scan_hits();
+        let width = hex_width + 2;
+        writeln!(
+            w,
+            "{}",
+            format!("{:width$}  {:8}  DESCRIPTION", "ADDRESS", "TYPE").cyan()
+        )?;
+        for hit in &hits {
+            let addr = format!("0x{:0hex_width$X}", hit.addr);
+            writeln!(
+                w,
+                "{}  {:8}  {}",
+                format!("{addr:width$}").yellow(),
+                hit.kind,
+                hit.description
+            )?;
+        }
+        let s = if hits.len() == 1 { "" } else { "s" };
+        writeln!(w)?;
+        writeln!(w, "{} signature{s} found", hits.len())?;
+        if self.show_path {
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+
+    pub fn print_scan(&self) -> Result<(), Error> {
+        let mut out = BufWriter::new(io::stdout().lock());
+        match self.write_scan(&mut out).and_then(|_| Ok(out.flush()?)) {
+            // the reader of the output went away (eg: | head)
+            Err(Error::Write(e)) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            result => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identify_test() {
+        let mut elf = b"\x7FELF\x02\x01\x01".to_vec();
+        elf.resize(20, 0);
+        elf[16] = 2;
+        elf[18] = 62;
+        assert_eq!(
+            identify(&elf),
+            Some(("ELF", 4, "64-bit LSB executable, x86-64".to_string()))
+        );
+        let mut png = b"\x89PNG\r\n\x1A\n\0\0\0\x0DIHDR".to_vec();
+        png.extend_from_slice(&[0, 0, 2, 128, 0, 0, 1, 224]);
+        assert_eq!(
+            identify(&png),
+            Some(("PNG", 8, "image, 640 x 480".to_string()))
+        );
+        // the name of a gzip member
+        let gzip = b"\x1F\x8B\x08\x08\0\0\0\0\0\x03vmlinux\0";
+        assert_eq!(identify(gzip).unwrap().2, "compressed data, name: vmlinux");
+        assert_eq!(identify(b"\x1F\x8B\x08\xFF"), None);
+        assert_eq!(identify(b"PK\x03\x04"), None);
+    }
+
+    #[test]
+    fn scan_hits_test() {
+        let mut data = vec![0; 3000];
+        data[100..106].copy_from_slice(&[0xFD, b'7', b'z', b'X', b'Z', 0]);
+        data[2000..2004].copy_from_slice(&[0x28, 0xB5, 0x2F, 0xFD]);
+        let mut dump = Dump::from_bytes(data).range(50..);
+        let hits = dump.scan_hits();
+        assert_eq!(
+            hits.iter().map(|h| (h.addr, h.kind)).collect::<Vec<_>>(),
+            [(100, "xz"), (2000, "zstd")]
+        );
+        dump.marks = hits;
+        assert!(dump.marked(105) && !dump.marked(106) && dump.marked(2000));
+    }
+}
@@ -0,0 +1,240 @@
+use super::{Dump, InputSize};
+use crate::Error;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+
+// Block devices and sparse files are only read in the dump range once check_args resolved it,
+// the holes of sparse files (SEEK_DATA/SEEK_HOLE) aren't read at all but shown as a note row
+
+pub(super) struct Sparse {
+    pub device: bool, // block device, else sparse regular file
+    pub loaded: bool, // the dump range was read
+}
+
+// size of the file at path and if it is a block device, None if the file is read as a whole
+#[cfg(unix)]
+pub(super) fn probe(path: &str) -> io::Result<Option<(u64, bool)>> {
+    use std::os::unix::fs::FileTypeExt;
+
+    let mut file = File::open(path)?;
+    let metadata = file.metadata()?;
+    if metadata.file_type().is_block_device() {
+        // the metadata of a block device has size 0, its end is found by seeking
+        return Ok(Some((file.seek(SeekFrom::End(0))?, true)));
+    }
+    let len = metadata.len();
+    let sparse = metadata.is_file() && !find_holes(&file, 0..len).is_empty();
+    Ok(sparse.then_some((len, false)))
+}
+
+#[cfg(not(unix))]
+pub(super) fn probe(_path: &str) -> io::Result<Option<(u64, bool)>> {
+    Ok(None)
+}
+
+// the holes of file in range (file offsets)
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "illumos",
+    target_os = "solaris"
+))]
+fn find_holes(file: &File, range: Range<u64>) -> Vec<Range<u64>> {
+    use std::os::fd::AsRawFd;
+
+    // None: no data follows offset (ENXIO), errors of file systems without holes end the search
+    let seek = |offset: u64, whence| -> Result<Option<u64>, ()> {
+        let pos = unsafe { libc::lseek(file.as_raw_fd(), offset as libc::off_t, whence) };
+        if pos >= 0 {
+            Ok(Some(pos as u64))
+        } else if io::Error::last_os_error().raw_os_error() == Some(libc::ENXIO) {
+            Ok(None)
+        } else {
+            Err(())
+        }
+    };
+
+    let mut holes = Vec::new();
+    let mut pos = range.start;
+    while pos < range.end {
+        let Ok(data) = seek(pos, libc::SEEK_DATA) else {
+            break;
+        };
+        let data = data.unwrap_or(range.end).min(range.end);
+        if data > pos {
+            holes.push(pos..data);
+        }
+        if data == range.end {
+            break;
+        }
+        match seek(data, libc::SEEK_HOLE) {
+            Ok(Some(hole)) => pos = hole,
+            _ => break,
+        }
+    }
+    holes
+}
+
+// without SEEK_DATA/SEEK_HOLE the holes can't be found: sparse files are read as a whole (their
+// holes as zero bytes) and block devices are read in the dump range
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "illumos",
+        target_os = "solaris"
+    ))
+))]
+fn find_holes(_file: &File, _range: Range<u64>) -> Vec<Range<u64>> {
+    Vec::new()
+}
+
+// reads range of file without the holes
+fn read_extents(file: &mut File, range: Range<u64>, holes: &[Range<u64>]) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    let mut pos = range.start;
+    for hole in holes.iter().chain([&(range.end..range.end)]) {
+        file.seek(SeekFrom::Start(pos))?;
+        let len = data.len();
+        data.resize(len + (hole.start - pos) as usize, 0);
+        file.read_exact(&mut data[len..])?;
+        pos = hole.end;
+    }
+    Ok(data)
+}
+
+impl Dump {
+    // dump of a block device or sparse file of size bytes, read by load_sparse
+    pub(super) fn from_sparse(path: &str, size: u64, device: bool) -> Dump {
+        let mut dump = Dump::from_layout(Vec::new(), 0, Vec::new());
+        dump.path = Some(path.to_string());
+        dump.filesize = size;
+        dump.file_end = size;
+        dump.num_bytes = size;
+        dump.sparse = Some(Sparse {
+            device,
+            loaded: false,
+        });
+        dump
+    }
+
+    // reads the dump range of the file, the data then only holds the dump range without holes
+    pub(super) fn load_sparse(&mut self) -> Result<(), Error> {
+        let Some(sparse) = self.sparse.as_mut().filter(|s| !s.loaded) else {
+            return Ok(());
+        };
+        let path = self.path.clone().unwrap_or_default();
+        let offset = self.start_addr - self.base_addr;
+        let range = offset..offset + self.num_bytes;
+        let read = || -> io::Result<(Vec<u8>, Vec<Range<u64>>)> {
+            let mut file = File::open(&path)?;
+            #[cfg(unix)]
+            let holes = find_holes(&file, range.clone());
+            #[cfg(not(unix))]
+            let holes = Vec::new();
+            Ok((read_extents(&mut file, range.clone(), &holes)?, holes))
+        };
+        let (data, holes) = read().map_err(|source| Error::Read { path, source })?;
+        sparse.loaded = true;
+
+        // the holes as addresses with the hole bytes before them
+        let mut before = 0;
+        self.holes = holes
+            .into_iter()
+            .map(|hole| {
+                let addr = hole.start - offset + self.start_addr;
+                let entry = (addr..addr + (hole.end - hole.start), before);
+                before += hole.end - hole.start;
+                entry
+            })
+            .collect();
+        self.input_size = InputSize::Total(self.filesize);
+        self.data = data;
+        self.base_addr = self.start_addr;
+        self.file_end = self.start_addr + self.num_bytes;
+        self.filesize = self.num_bytes;
+        Ok(())
+    }
+
+    // the note of the row that replaces the rows of a hole
+    pub(super) fn hole_text(hole: &Range<u64>, hex_width: usize) -> String {
+        format!(
+            "hole of {} zero bytes ({:0width$X} to {:0width$X})",
+            hole.end - hole.start,
+            hole.start,
+            hole.end - 1,
+            width = hex_width
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_extents_test() {
+        let path = std::env::temp_dir().join(format!("hexdump-sparse-{}", std::process::id()));
+        std::fs::write(&path, (0..64).collect::<Vec<u8>>()).unwrap();
+        let mut file = File::open(&path).unwrap();
+        let data = read_extents(&mut file, 8..48, &[16..24, 32..40]).unwrap();
+        let expected: Vec<u8> = (8..16).chain(24..32).chain(40..48).collect();
+        assert_eq!(data, expected);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "illumos",
+        target_os = "solaris"
+    ))]
+    #[test]
+    fn find_holes_test() {
+        use std::os::unix::fs::MetadataExt;
+
+        let path = std::env::temp_dir().join(format!("hexdump-holes-{}", std::process::id()));
+        let file = File::create(&path).unwrap();
+        // 1 MB hole followed by data
+        file.set_len(1 << 20).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .and_then(|mut f| io::Write::write_all(&mut f, &[1; 4096]))
+            .unwrap();
+        let file = File::open(&path).unwrap();
+        let allocated = file.metadata().unwrap().blocks() * 512;
+        let holes = find_holes(&file, 0..(1 << 20) + 4096);
+
+        // the file system of the temp dir doesn't support holes
+        if allocated >= 1 << 20 {
+            std::fs::remove_file(path).unwrap();
+            return;
+        }
+        assert_eq!(holes.len(), 1);
+        assert_eq!(holes[0], 0..1 << 20);
+
+        // the dump range is only read by load
+        let mut dump = Dump::new(&path.display().to_string(), &None, &None)
+            .unwrap()
+            .range(0x100000..0x100010);
+        assert!(matches!(dump.write_to(io::sink()), Err(Error::NotLoaded)));
+        dump.load().unwrap();
+        assert_eq!(dump.data, [1; 16]);
+        std::fs::remove_file(path).unwrap();
+    }
+}
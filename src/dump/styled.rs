@@ -0,0 +1,303 @@
+use super::encoding::Cell;
+use super::{Dump, Encoding, NOTE_TEXT_WIDTH};
+use crate::color::byte_color;
+use colored::Color;
+use unicode_width::UnicodeWidthChar;
+
+// The framed layout of print_dump as lines of styled text, used by the document output formats
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Plain,
+    Frame,
+    Index,
+    Addr,
+    Dark,
+    Highlight(Color),
+}
+
+impl Style {
+    // css class name and color of the style (same colors as the terminal output)
+    pub fn class(&self) -> &'static str {
+        match self {
+            Style::Plain => "plain",
+            Style::Frame => "frame",
+            Style::Index => "index",
+            Style::Addr => "addr",
+            Style::Dark => "dark",
+            Style::Highlight(Color::BrightGreen) => "bright-green",
+            Style::Highlight(Color::BrightRed) => "bright-red",
+            Style::Highlight(Color::BrightMagenta) => "bright-magenta",
+            Style::Highlight(Color::BrightBlue) => "bright-blue",
+            Style::Highlight(Color::Yellow) => "yellow",
+            Style::Highlight(_) => "plain",
+        }
+    }
+
+    pub fn rgb(&self) -> &'static str {
+        match self.class() {
+            "frame" => "#787878",
+            "index" => "#11a8cd",
+            "addr" | "yellow" => "#e5e510",
+            "dark" => "#404040",
+            "bright-green" => "#23d18b",
+            "bright-red" => "#f14c4c",
+            "bright-magenta" => "#d670d6",
+            "bright-blue" => "#3b8eea",
+            _ => "#cccccc",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+    pub offset: Option<u64>, // address of the byte this span shows (hex and ascii cells)
+}
+
+impl Span {
+    fn new(style: Style, text: impl Into<String>) -> Span {
+        Span {
+            text: text.into(),
+            style,
+            offset: None,
+        }
+    }
+
+    fn byte(style: Style, text: impl Into<String>, offset: u64) -> Span {
+        Span {
+            text: text.into(),
+            style,
+            offset: Some(offset),
+        }
+    }
+}
+
+pub type Line = Vec<Span>;
+
+// horizontal frame line, e.g. ┌──────┬───...┐, column is the width of the checksum column
+fn border(
+    left: &str,
+    cross: &str,
+    right: &str,
+    left_base_padding: usize,
+    right_base_padding: usize,
+    column: Option<usize>,
+) -> String {
+    let column = column.map_or(String::new(), |width| {
+        format!("{cross}{}", "─".repeat(width + 2))
+    });
+    format!(
+        "{left}{}────{}{cross}{}{cross}{}{cross}{}{column}{right}",
+        "─".repeat(left_base_padding),
+        "─".repeat(right_base_padding),
+        "─".repeat(32),
+        "─".repeat(32),
+        "─".repeat(18)
+    )
+}
+
+impl Dump {
+    pub(super) fn styled_lines(&self) -> Vec<Line> {
+        let mut lines: Vec<Line> = Vec::new();
+
+        if let Some(path_line) = self.path_line() {
+            lines.push(vec![Span::new(Style::Plain, path_line)]);
+        }
+        lines.push(vec![Span::new(Style::Plain, self.filesize_line())]);
+        let hex_width = self.hex_width();
+        lines.push(vec![Span::new(Style::Plain, self.dumping_line(hex_width))]);
+        for hash_line in self.hash_lines() {
+            lines.push(vec![Span::new(Style::Plain, hash_line)]);
+        }
+        lines.push(vec![]);
+        let left_base_padding = hex_width / 2;
+        let right_base_padding = hex_width - left_base_padding;
+        let column = self.block_hash_width();
+        let border = |left, cross, right| {
+            border(
+                left,
+                cross,
+                right,
+                left_base_padding,
+                right_base_padding,
+                column,
+            )
+        };
+
+        // dump head (and the checksum column of --block-hash)
+        lines.push(vec![Span::new(Style::Frame, border("┌", "┬", "┐"))]);
+        let mut head = vec![
+            Span::new(Style::Frame, "│"),
+            Span::new(
+                Style::Plain,
+                format!(
+                    "{}BASE{}",
+                    " ".repeat(left_base_padding),
+                    " ".repeat(right_base_padding)
+                ),
+            ),
+            Span::new(Style::Frame, "│ "),
+        ];
+        for i in 0..16 {
+            head.push(Span::new(Style::Index, format!("{:02X} ", i)));
+            if i == 7 {
+                head.push(Span::new(Style::Frame, "│"));
+                head.push(Span::new(Style::Plain, " "));
+            } else if i != 15 {
+                head.push(Span::new(Style::Plain, " "));
+            }
+        }
+        head.push(Span::new(Style::Frame, "│"));
+        head.push(Span::new(
+            Style::Plain,
+            format!(" {:^16} ", self.encoding.title()),
+        ));
+        head.push(Span::new(Style::Frame, "│"));
+        if let Some(((algo, _), width)) = self.block_hash.zip(column) {
+            head.push(Span::new(
+                Style::Plain,
+                format!(" {:^width$} ", algo.title()),
+            ));
+            head.push(Span::new(Style::Frame, "│"));
+        }
+        lines.push(head);
+        lines.push(vec![Span::new(Style::Frame, border("├", "┼", "┤"))]);
+
+        // dump rows, note rows show the bytes skipped between -r ranges and the memory
+        // mappings (--pid)
+        for (note, rows) in self.row_runs() {
+            if let Some(note) = note {
+                let width = NOTE_TEXT_WIDTH + column.map_or(0, |width| width + 3);
+                let text = Self::fit_note(&note, width);
+                lines.push(vec![
+                    Span::new(Style::Frame, "│"),
+                    Span::new(Style::Plain, "  "),
+                    Span::new(Style::Dark, "·".repeat(hex_width)),
+                    Span::new(Style::Plain, "  "),
+                    Span::new(Style::Frame, "│ "),
+                    Span::new(Style::Dark, format!("{text:width$}")),
+                    Span::new(Style::Frame, "│"),
+                ]);
+            }
+            for line in rows {
+                lines.push(self.styled_row(line * 16, hex_width));
+            }
+        }
+
+        // dump foot
+        lines.push(vec![Span::new(Style::Frame, border("└", "┴", "┘"))]);
+
+        lines
+    }
+
+    // the spans of the row at base
+    fn styled_row(&self, base: u64, hex_width: usize) -> Line {
+        let mut row = vec![
+            Span::new(Style::Frame, "│"),
+            Span::new(Style::Plain, "  "),
+            Span::new(Style::Addr, format!("{base:0hex_width$X}")),
+            Span::new(Style::Plain, "  "),
+            Span::new(Style::Frame, "│ "),
+        ];
+
+        for i in 0..16 {
+            let addr = base + i;
+            if !self.in_range(addr) {
+                row.push(Span::new(Style::Plain, "   "));
+            } else if let Some(data) = self.byte(addr) {
+                let style = match byte_color(data, self.visu_mode) {
+                    Some(color) => Style::Highlight(color),
+                    None => Style::Plain,
+                };
+                row.push(Span::byte(style, format!("{data:02X}"), addr));
+                row.push(Span::new(Style::Plain, " "));
+            } else {
+                row.push(Span::new(Style::Dark, "--"));
+                row.push(Span::new(Style::Plain, " "));
+            }
+
+            if i == 7 {
+                row.push(Span::new(Style::Frame, "│ "));
+            } else if i != 15 {
+                row.push(Span::new(Style::Plain, " "));
+            }
+        }
+
+        // text (ascii unless an encoding is set)
+        row.push(Span::new(Style::Frame, "│ "));
+        if self.encoding == Encoding::Ascii {
+            for i in 0..16 {
+                let addr = base + i;
+                if !self.in_range(addr) {
+                    row.push(Span::new(Style::Plain, " "));
+                } else {
+                    match self.byte(addr) {
+                        Some(byte) if byte.is_ascii_graphic() || byte == b' ' => {
+                            row.push(Span::byte(Style::Plain, (byte as char).to_string(), addr))
+                        }
+                        Some(_) => row.push(Span::byte(Style::Dark, ".", addr)),
+                        None => row.push(Span::new(Style::Plain, " ")),
+                    }
+                }
+            }
+        } else {
+            let mut cells = [Cell::Blank; 16];
+            self.text_cells(base, &mut cells);
+            for (addr, cell) in (base..).zip(cells) {
+                match cell {
+                    Cell::Blank => row.push(Span::new(Style::Plain, " ")),
+                    Cell::Dot => row.push(Span::byte(Style::Dark, ".", addr)),
+                    Cell::Char(c, span) => {
+                        let padding = " ".repeat(span - c.width().unwrap_or(1));
+                        row.push(Span::byte(Style::Plain, format!("{c}{padding}"), addr));
+                    }
+                    Cell::Spanned => {}
+                }
+            }
+        }
+        row.push(Span::new(Style::Frame, " │"));
+
+        // checksum of the block that ends in the row (--block-hash)
+        if let Some(width) = self.block_hash_width() {
+            let last = (base..base + 16).rev().find(|&addr| self.in_range(addr));
+            let checksum = last.and_then(|last| self.block_checksum(last));
+            let checksum = format!(" {:width$}", checksum.unwrap_or_default());
+            row.push(Span::new(Style::Plain, checksum));
+            row.push(Span::new(Style::Frame, " │"));
+        }
+        row
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn border_test() {
+        assert_eq!(
+            border("┌", "┬", "┐", 2, 2, None),
+            "┌────────┬────────────────────────────────┬────────────────────────────────┬──────────────────┐"
+        );
+        assert!(border("└", "┴", "┘", 2, 2, Some(8)).ends_with("┴──────────┘"));
+    }
+
+    #[test]
+    fn styled_encoding_test() {
+        let dump = Dump::from_bytes(b"caf\xE9".to_vec()).encoding(Encoding::Latin1);
+        let text = |line: &Line| {
+            line.iter()
+                .map(|span| span.text.as_str())
+                .collect::<String>()
+        };
+        let lines = dump.styled_lines();
+        let head = lines
+            .iter()
+            .find(|line| text(line).contains("BASE"))
+            .unwrap();
+        assert!(text(head).contains(" Latin-1 "));
+        assert!(text(&lines[lines.len() - 2]).contains("│ café "));
+    }
+}
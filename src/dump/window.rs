@@ -0,0 +1,139 @@
+use super::{AddrMode, Dump, InputSize};
+use crate::Error;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+
+// --head and --tail only read the part of the input that is dumped, so they work on inputs that
+// don't fit into memory (stdin is read up to --head or passed through a ring buffer for --tail)
+
+// the input at path (- is stdin) and its size if it is a regular file
+fn open(path: &str) -> io::Result<(Box<dyn Read>, Option<u64>)> {
+    if path == "-" {
+        return Ok((Box::new(io::stdin().lock()), None));
+    }
+    let file = File::open(path)?;
+    let metadata = file.metadata()?;
+    let size = metadata.is_file().then_some(metadata.len());
+    Ok((Box::new(file), size))
+}
+
+// the last n bytes of reader and the number of bytes it held
+fn read_tail(mut reader: impl Read, n: u64) -> io::Result<(Vec<u8>, u64)> {
+    let mut ring = VecDeque::new();
+    let mut buf = vec![0; 1 << 16];
+    let mut total = 0;
+    loop {
+        let read = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        total += read as u64;
+        ring.extend(&buf[..read]);
+        if ring.len() as u64 > n {
+            ring.drain(..ring.len() - n as usize);
+        }
+    }
+    Ok((ring.into(), total))
+}
+
+impl Dump {
+    /// Creates a dump of the first n bytes of the file at path (- reads stdin), the rest of the
+    /// file isn't read.
+    pub fn head(path: &str, n: u64) -> Result<Dump, Error> {
+        let read = || -> io::Result<(Vec<u8>, InputSize)> {
+            let (reader, size) = open(path)?;
+            // one more byte tells if a stream holds more than n bytes
+            let mut data = Vec::new();
+            reader.take(n.saturating_add(1)).read_to_end(&mut data)?;
+            let input_size = match size {
+                Some(size) => InputSize::Total(size),
+                None if data.len() as u64 <= n => InputSize::Data,
+                None => InputSize::Unknown,
+            };
+            data.truncate(n as usize);
+            Ok((data, input_size))
+        };
+        let (data, input_size) = read().map_err(|source| Error::Read {
+            path: path.to_string(),
+            source,
+        })?;
+        Ok(Dump::from_window(path, data, 0, input_size, AddrMode::Head))
+    }
+
+    /// Creates a dump of the last n bytes of the file at path (- reads stdin), regular files
+    /// are read from n bytes before their end, other inputs through a ring buffer of n bytes.
+    pub fn tail(path: &str, n: u64) -> Result<Dump, Error> {
+        let read = || -> io::Result<(Vec<u8>, u64)> {
+            if path == "-" {
+                return read_tail(io::stdin().lock(), n);
+            }
+            let mut file = File::open(path)?;
+            let metadata = file.metadata()?;
+            // block devices are seekable but have no size in their metadata
+            let size = match file.seek(SeekFrom::End(0)) {
+                _ if metadata.is_file() => metadata.len(),
+                Ok(size) if size > 0 => size,
+                _ => return read_tail(file, n),
+            };
+            let start = size.saturating_sub(n);
+            file.seek(SeekFrom::Start(start))?;
+            let mut data = Vec::new();
+            let read = file.read_to_end(&mut data)?;
+            Ok((data, start + read as u64))
+        };
+        let (data, total) = read().map_err(|source| Error::Read {
+            path: path.to_string(),
+            source,
+        })?;
+        let offset = total - data.len() as u64;
+        let input_size = InputSize::Total(total);
+        Ok(Dump::from_window(
+            path,
+            data,
+            offset,
+            input_size,
+            AddrMode::Tail,
+        ))
+    }
+
+    // dump of data, which was read from offset of the input at path
+    fn from_window(
+        path: &str,
+        data: Vec<u8>,
+        offset: u64,
+        input_size: InputSize,
+        addr_mode: AddrMode,
+    ) -> Dump {
+        // enable ansi support on windows (for colorful console output)
+        #[cfg(windows)]
+        crate::color::enable_ansi_support();
+
+        let mut dump = Dump::from_layout(data, offset, Vec::new());
+        dump.path = Some(path.to_string());
+        dump.data_offset = offset;
+        dump.input_size = input_size;
+        dump.addr_mode = addr_mode;
+        dump
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_tail_test() {
+        let data: Vec<u8> = (0..=255).cycle().take(200_000).collect();
+        let (tail, total) = read_tail(&data[..], 300).unwrap();
+        assert_eq!(total, 200_000);
+        assert_eq!(tail, &data[200_000 - 300..]);
+        assert_eq!(
+            read_tail(&data[..10], 300).unwrap(),
+            (data[..10].to_vec(), 10)
+        );
+        assert_eq!(read_tail(&data[..10], 0).unwrap(), (Vec::new(), 10));
+    }
+}
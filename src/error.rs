@@ -0,0 +1,372 @@
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum Error {
+    // usage errors
+    UnknownInputFormat(String),   // -i value does not exist
+    UnknownCompression(String),   // -z value does not exist
+    UnknownVisualization(String), // -v value does not exist
+    UnknownOutputFormat(String),  // -f value does not exist
+    UnknownHash(String),          // --hash or --block-hash value does not exist
+    InvalidBlockSize(u64),        // --block-size isn't a multiple of 16
+    UnknownBitOrder(String),      // --bits value does not exist
+    UnknownEncoding(String),      // --encoding value does not exist
+    BitRangeNeedsBits,            // bit granular -s or -n without --bits
+    InvalidArgCombination,        // -s -n -e were all passed
+    PngToTerminal,                // -f png without redirected stdout
+    PngTooLarge {
+        width: usize,
+        height: usize,
+    },
+    FollowNeedsFile,     // --follow without a single file
+    ProcessNeedsAddress, // --pid without --map, -s or -e
+    RawToTerminal,       // --extract without a file or redirected stdout
+    SplitNeedsFile,      // --split without an --extract file
+    ExtractSeveralFiles, // --extract of several files into one file
+    InvalidGlob {
+        pattern: String,
+        message: String,
+    },
+
+    // input errors
+    Read {
+        path: String,
+        source: io::Error,
+    },
+    Decode {
+        format: &'static str,
+        message: String,
+    },
+    Decompress {
+        format: &'static str,
+        message: String,
+    },
+    Records(String),
+    ProcessNotLoaded,
+    NotLoaded,
+
+    // range errors
+    EmptyFile,
+    BaseAddrOverflow {
+        base: u64,
+        filesize: u64,
+    },
+    FromEndExceedsFilesize {
+        arg: &'static str,
+        back: u64,
+        filesize: u64,
+    },
+    StartBeforeBase {
+        start: u64,
+        base: u64,
+    },
+    EndBeforeBase {
+        end: u64,
+        base: u64,
+    },
+    StartExceedsMax {
+        start: u64,
+        max: u64,
+    },
+    EndExceedsMax {
+        end: u64,
+        max: u64,
+    },
+    NumExceedsFilesize {
+        num: u64,
+        filesize: u64,
+    },
+    CalcEndExceedsMax {
+        end: u64,
+        max: u64,
+    },
+    StartAfterEnd {
+        start: u64,
+        end: u64,
+    },
+    NumExceedsEnd {
+        num: u64,
+        end: u64,
+    },
+    RangeExceedsData {
+        start: u64,
+        end: u64,
+        data_start: u64,
+        data_end: u64,
+    },
+    RangeReversed {
+        start: u64,
+        end: u64,
+    },
+    RecordAddressSpace {
+        end: u64,
+    },
+
+    // output errors
+    Create {
+        path: String,
+        source: io::Error,
+    },
+    Write(io::Error),
+}
+
+impl Error {
+    // usage and "Consider:" lines that are printed below the error
+    pub fn hints(&self) -> &'static [&'static str] {
+        match self {
+            Error::UnknownInputFormat(_) => &[
+                "-------------------------Usage--------------------------",
+                "-i auto:    Loads Intel HEX and S-record files, others raw",
+                "-i raw:     Dumps the bytes of the file as they are",
+                "-i ihex:    Loads the file as Intel HEX",
+                "-i srec:    Loads the file as Motorola S-record",
+                "-i base64:  Decodes the file from base64",
+                "-i base32:  Decodes the file from base32",
+                "-i ascii85: Decodes the file from ascii85",
+                "-i hex:     Decodes the file from a string of hex digits",
+                "-i escaped: Decodes the file from a \\x.. escaped string",
+            ],
+            Error::UnknownCompression(_) => &[
+                "-------------------------Usage--------------------------",
+                "-z:       Detects the compression format by its magic number",
+                "-z gzip:  Decompresses the file with gzip",
+                "-z zstd:  Decompresses the file with zstd",
+                "-z xz:    Decompresses the file with xz",
+                "-z bzip2: Decompresses the file with bzip2",
+            ],
+            Error::UnknownVisualization(_) => &[
+                "-------------------------Usage--------------------------",
+                "-v ascii:   Highlights ascii printable bytes",
+                "-v zeros:   Highlights bytes that have the value 0",
+                "-v high:    Highlights bytes that have a value >= 0x80",
+                "-v control: Highlights bytes that are control characters",
+            ],
+            Error::UnknownOutputFormat(_) => &[
+                "-------------------------Usage--------------------------",
+                "-f json:     Prints the dump as a single JSON document",
+                "-f jsonl:    Prints the dump as JSON lines (one per row)",
+                "-f html:     Prints the dump as a self-contained html page",
+                "-f svg:      Prints the dump as svg image",
+                "-f png:      Prints the dump as png image",
+                "-f md:       Prints the dump as markdown code block",
+                "-f md-table: Prints the dump as markdown table",
+                "-f ihex:     Prints the dumped range as Intel HEX file",
+                "-f srec:     Prints the dumped range as S-record file",
+            ],
+            Error::UnknownHash(_) => &[
+                "-------------------------Usage--------------------------",
+                "crc8:    CRC-8/SMBUS",
+                "crc16:   CRC-16/ARC",
+                "crc32:   CRC-32 (as used by zip, gzip and png)",
+                "adler32: Adler-32 (as used by zlib)",
+                "md5:     MD5",
+                "sha1:    SHA-1",
+                "sha256:  SHA-256",
+                "xxhash:  XXH64 with seed 0",
+            ],
+            Error::UnknownBitOrder(_) => &[
+                "-------------------------Usage--------------------------",
+                "--bits msb: Shows the most significant bit first (7 to 0)",
+                "--bits lsb: Shows the least significant bit first (0 to 7)",
+            ],
+            Error::UnknownEncoding(_) => &[
+                "-------------------------Usage--------------------------",
+                "--encoding ascii:    Shows printable ASCII bytes (default)",
+                "--encoding utf8:     Decodes UTF-8, also multi-byte characters",
+                "--encoding utf16le:  Decodes UTF-16 little endian",
+                "--encoding utf16be:  Decodes UTF-16 big endian",
+                "--encoding latin1:   Decodes ISO 8859-1",
+                "--encoding cp437:    Decodes code page 437 (IBM PC)",
+                "--encoding ebcdic:   Decodes EBCDIC (code page 037)",
+                "--encoding shiftjis: Decodes Shift-JIS",
+            ],
+            Error::BitRangeNeedsBits => &["Consider: add --bits to show the bits of the range"],
+            Error::InvalidBlockSize(_) => {
+                &["Consider: pass a multiple of 16 (eg: --block-size 512 for disk sectors)"]
+            }
+            Error::InvalidArgCombination => &[
+                "----------------------Usage----------------------",
+                "-s:    Prints data from address -s to end of file",
+                "-n:    Prints -n bytes starting from address 0",
+                "-e:    Prints data from address 0 to address -e",
+                "-s -n: Prints -n bytes starting from address -s",
+                "-s -e: Prints data from address -s to address -e",
+                "-n -e: Prints -n bytes back from address -e",
+                "-s -n -e: INVALID COMBINATION",
+            ],
+            Error::PngToTerminal => &["Consider: redirect the output into a file (eg: > dump.png)"],
+            Error::PngTooLarge { .. } => {
+                &["Consider: dump fewer bytes (eg: -n, -r) or use -f svg for large dumps"]
+            }
+            Error::FollowNeedsFile => &["Consider: pass one file path (stdin can't be followed)"],
+            Error::RawToTerminal => {
+                &["Consider: pass a file to --extract or redirect the output (eg: > blob.bin)"]
+            }
+            Error::SplitNeedsFile => &["Consider: pass the name of the parts (eg: -x blob.bin)"],
+            Error::ExtractSeveralFiles => {
+                &["Consider: extract to stdout (eg: -x - > blobs.bin) or one file per run"]
+            }
+            Error::ProcessNeedsAddress => {
+                &["Consider: --map heap, --map stack, --map <part of a path> or -s <address>"]
+            }
+            Error::BaseAddrOverflow { .. } => {
+                &["Consider: reduce --base-addr so that the file ends below 2^64"]
+            }
+            Error::FromEndExceedsFilesize { .. } => {
+                &["Consider: go back at most filesize bytes from the end"]
+            }
+            Error::StartExceedsMax { .. } => &["Consider: reduce -s to be in file range"],
+            Error::EndExceedsMax { .. } => &["Consider: reduce -e to be in file range"],
+            Error::NumExceedsFilesize { .. } => &["Consider: reduce -n to be in file range"],
+            Error::CalcEndExceedsMax { .. } => &[
+                "Consider: reduce -n to be in file range or drop -n arg to dump file from -s to eof",
+            ],
+            Error::StartAfterEnd { .. } => &["Consider: reduce -s to be <= -e"],
+            Error::NumExceedsEnd { .. } => &["Consider: reduce -n to be <= -e + 1"],
+            Error::RangeReversed { .. } => &["Consider: pass -r as start:end or start+length"],
+            _ => &[],
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::UnknownInputFormat(i) => {
+                write!(f, "unknown input format (-i {i} does not exist)")
+            }
+            Error::UnknownCompression(z) => {
+                write!(f, "unknown compression format (-z {z} does not exist)")
+            }
+            Error::UnknownVisualization(v) => {
+                write!(f, "unknown visualization (-v {v} does not exist)")
+            }
+            Error::UnknownOutputFormat(o) => {
+                write!(f, "unknown output format (-f {o} does not exist)")
+            }
+            Error::UnknownHash(h) => write!(f, "unknown hash ({h} does not exist)"),
+            Error::InvalidBlockSize(n) => {
+                write!(f, "block size (--block-size {n}) isn't a multiple of 16")
+            }
+            Error::UnknownBitOrder(b) => write!(f, "unknown bit order (--bits {b} does not exist)"),
+            Error::UnknownEncoding(e) => {
+                write!(f, "unknown encoding (--encoding {e} does not exist)")
+            }
+            Error::BitRangeNeedsBits => {
+                write!(
+                    f,
+                    "bit positions (-s, -n, -e with a bit suffix) need the bit view"
+                )
+            }
+            Error::InvalidArgCombination => write!(f, "invalid use of args (-s, -n, -e)"),
+            Error::PngToTerminal => write!(f, "can't print png data to the terminal"),
+            Error::PngTooLarge { width, height } => write!(
+                f,
+                "the png would be {width}x{height} pixels (it is rendered in memory, at most 64 megapixels)"
+            ),
+            Error::FollowNeedsFile => write!(f, "--follow needs a single file to watch"),
+            Error::ProcessNeedsAddress => {
+                write!(f, "--pid needs a mapping (--map) or an address (-s or -e)")
+            }
+            Error::RawToTerminal => write!(f, "can't print raw bytes to the terminal"),
+            Error::SplitNeedsFile => write!(f, "--split needs a file to --extract to"),
+            Error::ExtractSeveralFiles => {
+                write!(f, "several files can only be extracted to stdout")
+            }
+            Error::InvalidGlob { pattern, message } => {
+                write!(f, "invalid pattern {pattern} ({message})")
+            }
+            Error::Read { path, source } => write!(f, "can't read {path} ({source})"),
+            Error::Decode { format, message } => write!(f, "invalid {format} input ({message})"),
+            Error::Decompress { format, message } => {
+                write!(f, "can't decompress {format} input ({message})")
+            }
+            Error::Records(message) => write!(f, "invalid record file ({message})"),
+            Error::ProcessNotLoaded => {
+                write!(f, "the process memory wasn't read (Dump::load reads it)")
+            }
+            Error::NotLoaded => {
+                write!(
+                    f,
+                    "the block device or sparse file wasn't read (Dump::load reads the dump range)"
+                )
+            }
+            Error::EmptyFile => write!(f, "the file is empty (-s and -e have no byte to address)"),
+            Error::BaseAddrOverflow { base, filesize } => write!(
+                f,
+                "base_addr (--base-addr {base:X}) + filesize ({filesize}) exceeds the 64 bit address space"
+            ),
+            Error::FromEndExceedsFilesize {
+                arg,
+                back,
+                filesize,
+            } => write!(
+                f,
+                "{arg} -{back} lies before the first byte of the file (filesize: {filesize})"
+            ),
+            Error::StartBeforeBase { start, base } => write!(
+                f,
+                "start_addr (-s {start}) is smaller than the first address ({base})"
+            ),
+            Error::EndBeforeBase { end, base } => write!(
+                f,
+                "end_addr (-e {end}) is smaller than the first address ({base})"
+            ),
+            Error::StartExceedsMax { start, max } => {
+                write!(f, "start_addr (-s {start}) exceeds file_max_index ({max})")
+            }
+            Error::EndExceedsMax { end, max } => {
+                write!(f, "end_addr (-e {end}) exceeds file_max_index ({max})")
+            }
+            Error::NumExceedsFilesize { num, filesize } => {
+                write!(f, "num_bytes (-n {num}) exceeds filesize ({filesize})")
+            }
+            Error::CalcEndExceedsMax { end, max } => write!(
+                f,
+                "calculated end_addr ({end}) exceeds file_max_index ({max})"
+            ),
+            Error::StartAfterEnd { start, end } => write!(
+                f,
+                "start_addr (-s {start}) is bigger than end_addr (-e {end})"
+            ),
+            Error::NumExceedsEnd { num, end } => {
+                write!(f, "num_bytes (-n {num}) is bigger than end_addr (-e {end})")
+            }
+            Error::RangeExceedsData {
+                start,
+                end,
+                data_start,
+                data_end,
+            } => write!(
+                f,
+                "range {start:X}..{end:X} exceeds the data ({data_start:X}..{data_end:X})"
+            ),
+            Error::RangeReversed { start, end } => {
+                write!(f, "range {start:X}:{end:X} ends before it starts (-r)")
+            }
+            Error::RecordAddressSpace { end } => write!(
+                f,
+                "end_addr ({end:X}) exceeds the 32 bit address space of the record format"
+            ),
+            Error::Create { path, source } => write!(f, "can't create {path} ({source})"),
+            Error::Write(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Read { source, .. } | Error::Create { source, .. } => Some(source),
+            Error::Write(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Write(e)
+    }
+}
@@ -0,0 +1,10 @@
+pub mod args;
+#[macro_use]
+mod color;
+mod dump;
+mod error;
+pub mod paths;
+
+pub use args::Cli;
+pub use dump::{BitOrder, Dump, Encoding, HashAlgo, OutputFormat, VisuMode};
+pub use error::Error;
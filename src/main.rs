@@ -1,14 +1,173 @@
-mod args;
-#[macro_use]
-mod color;
-mod dump;
+use hexdump::args::Addr;
+use hexdump::{Cli, Dump, Error, paths, println_error, println_info};
 
-use args::Cli;
-use dump::*;
+// exit codes by the kind of error, so wrappers can tell them apart
+fn exit_code(error: &Error) -> i32 {
+    match error {
+        Error::UnknownInputFormat(_)
+        | Error::UnknownCompression(_)
+        | Error::UnknownVisualization(_)
+        | Error::UnknownOutputFormat(_)
+        | Error::UnknownHash(_)
+        | Error::UnknownBitOrder(_)
+        | Error::UnknownEncoding(_)
+        | Error::BitRangeNeedsBits
+        | Error::InvalidBlockSize(_)
+        | Error::InvalidArgCombination
+        | Error::PngToTerminal
+        | Error::PngTooLarge { .. }
+        | Error::FollowNeedsFile
+        | Error::ProcessNeedsAddress
+        | Error::RawToTerminal
+        | Error::SplitNeedsFile
+        | Error::ExtractSeveralFiles
+        | Error::InvalidGlob { .. } => 2,
+        Error::Read { .. } | Error::ProcessNotLoaded | Error::NotLoaded => 3,
+        Error::Decode { .. } | Error::Decompress { .. } | Error::Records(_) => 4,
+        Error::EmptyFile
+        | Error::BaseAddrOverflow { .. }
+        | Error::FromEndExceedsFilesize { .. }
+        | Error::StartBeforeBase { .. }
+        | Error::EndBeforeBase { .. }
+        | Error::StartExceedsMax { .. }
+        | Error::EndExceedsMax { .. }
+        | Error::NumExceedsFilesize { .. }
+        | Error::CalcEndExceedsMax { .. }
+        | Error::StartAfterEnd { .. }
+        | Error::NumExceedsEnd { .. }
+        | Error::RangeExceedsData { .. }
+        | Error::RangeReversed { .. }
+        | Error::RecordAddressSpace { .. } => 5,
+        Error::Create { .. } | Error::Write(_) => 1,
+    }
+}
+
+// dumps the file at path, returns the number of dumped bytes
+fn dump_file(cli: &Cli, path: &String, show_path: bool) -> Result<u64, Error> {
+    let dump = match (cli.head, cli.tail) {
+        (Some(n), _) => Dump::head(path, n)?,
+        (_, Some(n)) => Dump::tail(path, n)?,
+        _ => Dump::new(path, &cli.input, &cli.decompress)?,
+    };
+    let mut dump = dump.show_path(show_path);
+    dump.check_args(cli)?;
+    output(cli, &dump)?;
+    Ok(dump.dumped_bytes())
+}
+
+// prints the dump, the signatures found (--scan) or writes the dumped bytes raw (--extract)
+fn output(cli: &Cli, dump: &Dump) -> Result<(), Error> {
+    if cli.scan {
+        return dump.print_scan();
+    }
+    let Some(path) = &cli.extract else {
+        return dump.print();
+    };
+    let paths = dump.extract(path, cli.split)?;
+    match paths.as_slice() {
+        [] => {}
+        [path] => println_info!("Extracted {} bytes to {path}", dump.dumped_bytes()),
+        [first, .., last] => println_info!(
+            "Extracted {} bytes to {} files ({first} to {last})",
+            dump.dumped_bytes(),
+            paths.len()
+        ),
+    }
+    Ok(())
+}
+
+// dumps the end of the file at path and then the bytes appended to it
+fn follow_file(cli: &Cli, path: &str, rows: u64) -> Result<(), Error> {
+    // the appended bytes are dumped as they are, only the last rows of the file are read
+    let mut dump = Dump::for_follow(path)?;
+    dump.check_args(cli)?;
+    dump.follow(rows)
+}
+
+// dumps the memory of the process pid
+fn dump_process(cli: &Cli, pid: u32) -> Result<(), Error> {
+    // an absolute -s or -e selects the mapping without --map
+    let addr = [cli.start, cli.end]
+        .into_iter()
+        .find_map(|addr| match addr {
+            Some(Addr::Absolute(addr)) => Some(addr),
+            Some(Addr::Bit(bit)) => Some(bit / 8),
+            _ => None,
+        });
+    let mut dump = Dump::process(pid, cli.map.as_deref(), addr)?;
+    dump.check_args(cli)?;
+    output(cli, &dump)
+}
+
+fn report(error: &Error, path: Option<&String>) {
+    match path {
+        Some(path) => println_error!("Error: {path}: {error}"),
+        None => println_error!("Error: {error}"),
+    }
+    for hint in error.hints() {
+        println_info!("{hint}");
+    }
+}
 
 fn main() {
     let cli = Cli::get_args();
-    let mut dump = Dump::new(&cli.file);
-    dump.check_args(&cli);
-    dump.print_dump();
+    if let Some(pid) = cli.pid {
+        if let Err(e) = dump_process(&cli, pid) {
+            report(&e, None);
+            std::process::exit(exit_code(&e));
+        }
+        return;
+    }
+
+    let paths = paths::expand(&cli.files, cli.recursive);
+
+    if let Some(rows) = cli.follow {
+        let result = match <[_; 1]>::try_from(paths) {
+            Ok([path]) => path.and_then(|path| follow_file(&cli, &path, rows)),
+            Err(_) => Err(Error::FollowNeedsFile),
+        };
+        if let Err(e) = result {
+            report(&e, None);
+            std::process::exit(exit_code(&e));
+        }
+        return;
+    }
+
+    // with several files every dump gets its File: line, errors don't stop the other files
+    let several = paths.len() > 1;
+    if several && cli.extract.as_ref().is_some_and(|path| path != "-") {
+        let e = Error::ExtractSeveralFiles;
+        report(&e, None);
+        std::process::exit(exit_code(&e));
+    }
+    let mut exit = 0;
+    let (mut dumped_files, mut dumped_bytes) = (0, 0);
+    for path in &paths {
+        // an arg or a path below a glob or a directory that can't be read
+        let path = match path {
+            Ok(path) => path,
+            Err(e) => {
+                report(e, None);
+                exit = exit_code(e);
+                continue;
+            }
+        };
+        match dump_file(&cli, path, several) {
+            Ok(bytes) => {
+                dumped_files += 1;
+                dumped_bytes += bytes;
+            }
+            Err(e) => {
+                report(&e, several.then_some(path));
+                exit = exit_code(&e);
+            }
+        }
+    }
+    if several {
+        println_info!(
+            "Dumped {dumped_files} of {} files ({dumped_bytes} bytes)",
+            paths.len()
+        );
+    }
+    std::process::exit(exit);
 }
@@ -0,0 +1,159 @@
+use crate::Error;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// The FILE args expanded to the files that are dumped: globs are matched (shells on windows
+// don't expand them) and directories are walked with --recursive
+
+// a path of the expansion or the error of a path that can't be read, it is reported like
+// the errors of the dump of a file
+type PathResult = Result<String, Error>;
+
+// an existing file is taken as it is, even with glob characters in its name (eg: x[1].bin)
+fn is_glob(arg: &str) -> bool {
+    arg.contains(['*', '?', '[']) && !Path::new(arg).exists()
+}
+
+/// Expands the FILE args to the paths of the files to dump, in the order they were passed.
+/// Globs are matched in sorted order and directories are walked if recursive is set. Args and
+/// paths that can't be dumped (eg: a glob without matches, a directory without recursive) are
+/// returned as their errors, so the other paths are still dumped.
+pub fn expand(args: &[String], recursive: bool) -> Vec<PathResult> {
+    let mut paths = Vec::new();
+    for arg in args {
+        if arg == "-" || !is_glob(arg) {
+            push_path(&mut paths, arg.clone(), recursive, true);
+            continue;
+        }
+
+        let matches = match glob::glob(arg) {
+            Ok(matches) => matches,
+            Err(e) => {
+                paths.push(Err(Error::InvalidGlob {
+                    pattern: arg.clone(),
+                    message: e.msg.to_string(),
+                }));
+                continue;
+            }
+        };
+        let count = paths.len();
+        for entry in matches {
+            match entry {
+                // directories matched by a glob are only dumped with --recursive
+                Ok(path) => push_path(&mut paths, path.display().to_string(), recursive, false),
+                Err(e) => paths.push(Err(Error::Read {
+                    path: e.path().display().to_string(),
+                    source: e.into(),
+                })),
+            }
+        }
+        if paths.len() == count {
+            paths.push(Err(Error::Read {
+                path: arg.clone(),
+                source: io::Error::new(io::ErrorKind::NotFound, "no file matches the pattern"),
+            }));
+        }
+    }
+    paths
+}
+
+// adds path (or the files below it if it is a directory)
+fn push_path(paths: &mut Vec<PathResult>, path: String, recursive: bool, explicit: bool) {
+    if path == "-" || !Path::new(&path).is_dir() {
+        paths.push(Ok(path));
+    } else if recursive {
+        walk(paths, Path::new(&path));
+    } else if explicit {
+        paths.push(Err(Error::Read {
+            path,
+            source: io::Error::other("is a directory, pass --recursive to dump its files"),
+        }));
+    }
+}
+
+// adds the files below dir in sorted order, a directory that can't be read is added as its
+// error. Symbolic links to directories aren't followed (they could loop, eg: d/loop -> ..)
+fn walk(paths: &mut Vec<PathResult>, dir: &Path) {
+    let entries = fs::read_dir(dir).and_then(|entries| {
+        entries
+            .map(|e| e.and_then(|e| Ok((e.path(), e.file_type()?))))
+            .collect::<io::Result<Vec<_>>>()
+    });
+    let mut entries = match entries {
+        Ok(entries) => entries,
+        Err(source) => {
+            paths.push(Err(Error::Read {
+                path: dir.display().to_string(),
+                source,
+            }));
+            return;
+        }
+    };
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    for (entry, file_type) in entries {
+        if file_type.is_dir() {
+            walk(paths, &entry);
+        } else if !(file_type.is_symlink() && entry.is_dir()) {
+            paths.push(Ok(entry.display().to_string()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_test() {
+        let dir = std::env::temp_dir().join(format!("hexdump-paths-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        for file in ["b.bin", "a.bin", "c.txt", "sub/d.bin"] {
+            fs::write(dir.join(file), [0]).unwrap();
+        }
+        let arg = |p: &str| dir.join(p).display().to_string();
+        let expand = |args: &[String], recursive| {
+            expand(args, recursive)
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        assert_eq!(
+            expand(&[arg("*.bin")], false).unwrap(),
+            [arg("a.bin"), arg("b.bin")]
+        );
+        assert_eq!(
+            expand(&[arg("*")], true).unwrap(),
+            [arg("a.bin"), arg("b.bin"), arg("c.txt"), arg("sub/d.bin")]
+        );
+
+        // a file with glob characters in its name
+        fs::write(dir.join("x[1].bin"), [0]).unwrap();
+        assert_eq!(
+            expand(&[arg("x[1].bin")], false).unwrap(),
+            [arg("x[1].bin")]
+        );
+
+        // a symbolic link to a parent directory isn't walked
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("..", dir.join("sub/loop")).unwrap();
+            assert_eq!(expand(&[arg("sub")], true).unwrap(), [arg("sub/d.bin")]);
+        }
+        assert!(matches!(expand(&[arg("")], false), Err(Error::Read { .. })));
+        assert!(matches!(
+            expand(&[arg("*.png")], false),
+            Err(Error::Read { .. })
+        ));
+        // the args that can't be dumped don't stop the others
+        let paths = super::expand(&[arg("*.png"), arg("a.bin"), arg("sub")], false);
+        assert!(matches!(
+            paths.as_slice(),
+            [Err(Error::Read { .. }), Ok(a), Err(Error::Read { .. })] if *a == arg("a.bin")
+        ));
+        assert_eq!(expand(&["-".to_string()], true).unwrap(), ["-"]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}